@@ -1,7 +1,40 @@
+use std::env;
 use std::io;
+use std::path::Path;
 
 mod generate_ast;
 
+// Set LOX_AST_SNAPSHOT=check to fail the build when the committed copy of the
+// generated AST is out of date, or LOX_AST_SNAPSHOT=update to refresh it.
+const SNAPSHOT_DIR: &str = "generate_ast/generated";
+
 fn main() -> io::Result<()> {
-    generate_ast::generate_ast("src")
+    let grammar_dir = Path::new("generate_ast");
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=generate_ast/mod.rs");
+    for grammar in generate_ast::grammar_files(grammar_dir) {
+        println!("cargo:rerun-if-changed={}", grammar.display());
+    }
+    println!("cargo:rerun-if-changed={SNAPSHOT_DIR}");
+    println!("cargo:rerun-if-env-changed=LOX_AST_SNAPSHOT");
+
+    let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo");
+    generate_ast::generate_ast(grammar_dir, Path::new(&out_dir))?;
+
+    match env::var("LOX_AST_SNAPSHOT").as_deref() {
+        Ok("check") => {
+            let stale = generate_ast::check_ast(grammar_dir, Path::new(SNAPSHOT_DIR))?;
+            if !stale.is_empty() {
+                return Err(io::Error::other(format!(
+                    "{SNAPSHOT_DIR} is out of date ({}); rebuild with LOX_AST_SNAPSHOT=update",
+                    stale.join(", ")
+                )));
+            }
+        }
+        Ok("update") => generate_ast::generate_ast(grammar_dir, Path::new(SNAPSHOT_DIR))?,
+        _ => {}
+    }
+
+    Ok(())
 }
@@ -0,0 +1,31 @@
+#![no_main]
+
+// Anything that parses must format to source that parses again and formats
+// the same way; the text is compared since line numbers differ between trees
+use libfuzzer_sys::fuzz_target;
+use lox_ast::formatter::Formatter;
+use lox_ast::parser::Parser;
+use lox_ast::scanner::Scanner;
+
+fuzz_target!(|source: &str| {
+    let mut scanner = Scanner::new(source.to_string());
+    let Ok(tokens) = scanner.scan_tokens() else {
+        return;
+    };
+    let Ok(statements) = Parser::new(tokens).parse() else {
+        return;
+    };
+    let formatted = Formatter
+        .format(&statements)
+        .expect("formatting can't fail");
+    let mut scanner = Scanner::new(formatted.clone());
+    let tokens = scanner.scan_tokens().expect("formatted source should scan");
+    let reparsed = Parser::new(tokens)
+        .parse()
+        .expect("formatted source should parse");
+    assert_eq!(
+        Formatter.format(&reparsed).unwrap(),
+        formatted,
+        "formatting is not stable"
+    );
+});
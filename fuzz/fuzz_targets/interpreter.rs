@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lox_ast::interpreter::Interpreter;
+use lox_ast::optimizer::ConstantFolder;
+use lox_ast::parser::Parser;
+use lox_ast::scanner::Scanner;
+
+fuzz_target!(|source: &str| {
+    let mut scanner = Scanner::new(source.to_string());
+    let Ok(tokens) = scanner.scan_tokens() else {
+        return;
+    };
+    let Ok(statements) = Parser::new(tokens).parse() else {
+        return;
+    };
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(Box::new(std::io::sink()));
+    if let Ok(statements) = ConstantFolder::new(&interpreter).optimize(statements) {
+        let _ = interpreter.interpret(&statements);
+    }
+});
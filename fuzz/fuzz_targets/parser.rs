@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lox_ast::parser::Parser;
+use lox_ast::scanner::Scanner;
+
+fuzz_target!(|source: &str| {
+    let mut scanner = Scanner::new(source.to_string());
+    if let Ok(tokens) = scanner.scan_tokens() {
+        let _ = Parser::new(tokens).parse();
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lox_ast::scanner::Scanner;
+
+fuzz_target!(|source: &str| {
+    let _ = Scanner::new(source.to_string()).scan_tokens();
+});
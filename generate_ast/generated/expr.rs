@@ -0,0 +1,673 @@
+use crate::error::*;
+use crate::object::*;
+use crate::token::*;
+
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Assign(AssignExpr),
+    Binary(BinaryExpr),
+    Call(CallExpr),
+    Get(GetExpr),
+    Grouping(GroupingExpr),
+    Interpolate(InterpolateExpr),
+    Index(IndexExpr),
+    List(ListExpr),
+    Literal(LiteralExpr),
+    Map(MapExpr),
+    Set(SetExpr),
+    SetIndex(SetIndexExpr),
+    Unary(UnaryExpr),
+    Variable(VariableExpr),
+}
+
+impl Expr {
+    pub fn accept<T>(&self, expr_visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        match self {
+            Expr::Assign(x) => x.accept(expr_visitor),
+            Expr::Binary(x) => x.accept(expr_visitor),
+            Expr::Call(x) => x.accept(expr_visitor),
+            Expr::Get(x) => x.accept(expr_visitor),
+            Expr::Grouping(x) => x.accept(expr_visitor),
+            Expr::Interpolate(x) => x.accept(expr_visitor),
+            Expr::Index(x) => x.accept(expr_visitor),
+            Expr::List(x) => x.accept(expr_visitor),
+            Expr::Literal(x) => x.accept(expr_visitor),
+            Expr::Map(x) => x.accept(expr_visitor),
+            Expr::Set(x) => x.accept(expr_visitor),
+            Expr::SetIndex(x) => x.accept(expr_visitor),
+            Expr::Unary(x) => x.accept(expr_visitor),
+            Expr::Variable(x) => x.accept(expr_visitor),
+        }
+    }
+
+    pub fn accept_mut<T>(&self, expr_visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        match self {
+            Expr::Assign(x) => x.accept_mut(expr_visitor),
+            Expr::Binary(x) => x.accept_mut(expr_visitor),
+            Expr::Call(x) => x.accept_mut(expr_visitor),
+            Expr::Get(x) => x.accept_mut(expr_visitor),
+            Expr::Grouping(x) => x.accept_mut(expr_visitor),
+            Expr::Interpolate(x) => x.accept_mut(expr_visitor),
+            Expr::Index(x) => x.accept_mut(expr_visitor),
+            Expr::List(x) => x.accept_mut(expr_visitor),
+            Expr::Literal(x) => x.accept_mut(expr_visitor),
+            Expr::Map(x) => x.accept_mut(expr_visitor),
+            Expr::Set(x) => x.accept_mut(expr_visitor),
+            Expr::SetIndex(x) => x.accept_mut(expr_visitor),
+            Expr::Unary(x) => x.accept_mut(expr_visitor),
+            Expr::Variable(x) => x.accept_mut(expr_visitor),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AssignExpr {
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BinaryExpr {
+    pub left: Box<Expr>,
+    pub operator: Token,
+    pub right: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CallExpr {
+    pub callee: Box<Expr>,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GetExpr {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GroupingExpr {
+    pub expression: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InterpolateExpr {
+    pub string: Token,
+    pub parts: Vec<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct IndexExpr {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ListExpr {
+    pub bracket: Token,
+    pub elements: Vec<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LiteralExpr {
+    pub value: Option<Object>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MapExpr {
+    pub brace: Token,
+    pub keys: Vec<Expr>,
+    pub values: Vec<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SetExpr {
+    pub object: Box<Expr>,
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SetIndexExpr {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnaryExpr {
+    pub operator: Token,
+    pub right: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VariableExpr {
+    pub name: Token,
+}
+
+pub trait ExprVisitor<T> {
+    fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<T, LoxError>;
+    fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<T, LoxError>;
+    fn visit_call_expr(&self, expr: &CallExpr) -> Result<T, LoxError>;
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<T, LoxError>;
+    fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<T, LoxError>;
+    fn visit_interpolate_expr(&self, expr: &InterpolateExpr) -> Result<T, LoxError>;
+    fn visit_index_expr(&self, expr: &IndexExpr) -> Result<T, LoxError>;
+    fn visit_list_expr(&self, expr: &ListExpr) -> Result<T, LoxError>;
+    fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<T, LoxError>;
+    fn visit_map_expr(&self, expr: &MapExpr) -> Result<T, LoxError>;
+    fn visit_set_expr(&self, expr: &SetExpr) -> Result<T, LoxError>;
+    fn visit_set_index_expr(&self, expr: &SetIndexExpr) -> Result<T, LoxError>;
+    fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<T, LoxError>;
+    fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<T, LoxError>;
+}
+
+pub trait ExprVisitorMut<T> {
+    fn visit_assign_expr(&mut self, expr: &AssignExpr) -> Result<T, LoxError>;
+    fn visit_binary_expr(&mut self, expr: &BinaryExpr) -> Result<T, LoxError>;
+    fn visit_call_expr(&mut self, expr: &CallExpr) -> Result<T, LoxError>;
+    fn visit_get_expr(&mut self, expr: &GetExpr) -> Result<T, LoxError>;
+    fn visit_grouping_expr(&mut self, expr: &GroupingExpr) -> Result<T, LoxError>;
+    fn visit_interpolate_expr(&mut self, expr: &InterpolateExpr) -> Result<T, LoxError>;
+    fn visit_index_expr(&mut self, expr: &IndexExpr) -> Result<T, LoxError>;
+    fn visit_list_expr(&mut self, expr: &ListExpr) -> Result<T, LoxError>;
+    fn visit_literal_expr(&mut self, expr: &LiteralExpr) -> Result<T, LoxError>;
+    fn visit_map_expr(&mut self, expr: &MapExpr) -> Result<T, LoxError>;
+    fn visit_set_expr(&mut self, expr: &SetExpr) -> Result<T, LoxError>;
+    fn visit_set_index_expr(&mut self, expr: &SetIndexExpr) -> Result<T, LoxError>;
+    fn visit_unary_expr(&mut self, expr: &UnaryExpr) -> Result<T, LoxError>;
+    fn visit_variable_expr(&mut self, expr: &VariableExpr) -> Result<T, LoxError>;
+}
+
+impl AssignExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_assign_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_assign_expr(self)
+    }
+}
+
+impl BinaryExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_binary_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_binary_expr(self)
+    }
+}
+
+impl CallExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_call_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_call_expr(self)
+    }
+}
+
+impl GetExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_get_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_get_expr(self)
+    }
+}
+
+impl GroupingExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_grouping_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_grouping_expr(self)
+    }
+}
+
+impl InterpolateExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_interpolate_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_interpolate_expr(self)
+    }
+}
+
+impl IndexExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_index_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_index_expr(self)
+    }
+}
+
+impl ListExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_list_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_list_expr(self)
+    }
+}
+
+impl LiteralExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_literal_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_literal_expr(self)
+    }
+}
+
+impl MapExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_map_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_map_expr(self)
+    }
+}
+
+impl SetExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_set_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_set_expr(self)
+    }
+}
+
+impl SetIndexExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_set_index_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_set_index_expr(self)
+    }
+}
+
+impl UnaryExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_unary_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_unary_expr(self)
+    }
+}
+
+impl VariableExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_variable_expr(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn ExprVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_variable_expr(self)
+    }
+}
+
+pub trait Fold {
+    fn fold_expr(&mut self, expr: Expr) -> Result<Expr, LoxError> {
+        fold_expr(self, expr)
+    }
+
+    fn fold_assign_expr(&mut self, expr: AssignExpr) -> Result<Expr, LoxError> {
+        fold_assign_expr(self, expr)
+    }
+
+    fn fold_binary_expr(&mut self, expr: BinaryExpr) -> Result<Expr, LoxError> {
+        fold_binary_expr(self, expr)
+    }
+
+    fn fold_call_expr(&mut self, expr: CallExpr) -> Result<Expr, LoxError> {
+        fold_call_expr(self, expr)
+    }
+
+    fn fold_get_expr(&mut self, expr: GetExpr) -> Result<Expr, LoxError> {
+        fold_get_expr(self, expr)
+    }
+
+    fn fold_grouping_expr(&mut self, expr: GroupingExpr) -> Result<Expr, LoxError> {
+        fold_grouping_expr(self, expr)
+    }
+
+    fn fold_interpolate_expr(&mut self, expr: InterpolateExpr) -> Result<Expr, LoxError> {
+        fold_interpolate_expr(self, expr)
+    }
+
+    fn fold_index_expr(&mut self, expr: IndexExpr) -> Result<Expr, LoxError> {
+        fold_index_expr(self, expr)
+    }
+
+    fn fold_list_expr(&mut self, expr: ListExpr) -> Result<Expr, LoxError> {
+        fold_list_expr(self, expr)
+    }
+
+    fn fold_literal_expr(&mut self, expr: LiteralExpr) -> Result<Expr, LoxError> {
+        fold_literal_expr(self, expr)
+    }
+
+    fn fold_map_expr(&mut self, expr: MapExpr) -> Result<Expr, LoxError> {
+        fold_map_expr(self, expr)
+    }
+
+    fn fold_set_expr(&mut self, expr: SetExpr) -> Result<Expr, LoxError> {
+        fold_set_expr(self, expr)
+    }
+
+    fn fold_set_index_expr(&mut self, expr: SetIndexExpr) -> Result<Expr, LoxError> {
+        fold_set_index_expr(self, expr)
+    }
+
+    fn fold_unary_expr(&mut self, expr: UnaryExpr) -> Result<Expr, LoxError> {
+        fold_unary_expr(self, expr)
+    }
+
+    fn fold_variable_expr(&mut self, expr: VariableExpr) -> Result<Expr, LoxError> {
+        fold_variable_expr(self, expr)
+    }
+}
+
+pub fn fold_expr<F: Fold + ?Sized>(folder: &mut F, expr: Expr) -> Result<Expr, LoxError> {
+    match expr {
+        Expr::Assign(x) => folder.fold_assign_expr(x),
+        Expr::Binary(x) => folder.fold_binary_expr(x),
+        Expr::Call(x) => folder.fold_call_expr(x),
+        Expr::Get(x) => folder.fold_get_expr(x),
+        Expr::Grouping(x) => folder.fold_grouping_expr(x),
+        Expr::Interpolate(x) => folder.fold_interpolate_expr(x),
+        Expr::Index(x) => folder.fold_index_expr(x),
+        Expr::List(x) => folder.fold_list_expr(x),
+        Expr::Literal(x) => folder.fold_literal_expr(x),
+        Expr::Map(x) => folder.fold_map_expr(x),
+        Expr::Set(x) => folder.fold_set_expr(x),
+        Expr::SetIndex(x) => folder.fold_set_index_expr(x),
+        Expr::Unary(x) => folder.fold_unary_expr(x),
+        Expr::Variable(x) => folder.fold_variable_expr(x),
+    }
+}
+
+pub fn fold_assign_expr<F: Fold + ?Sized>(folder: &mut F, expr: AssignExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Assign(AssignExpr {
+        name: expr.name,
+        value: Box::new(folder.fold_expr(*expr.value)?),
+    }))
+}
+
+pub fn fold_binary_expr<F: Fold + ?Sized>(folder: &mut F, expr: BinaryExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Binary(BinaryExpr {
+        left: Box::new(folder.fold_expr(*expr.left)?),
+        operator: expr.operator,
+        right: Box::new(folder.fold_expr(*expr.right)?),
+    }))
+}
+
+pub fn fold_call_expr<F: Fold + ?Sized>(folder: &mut F, expr: CallExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Call(CallExpr {
+        callee: Box::new(folder.fold_expr(*expr.callee)?),
+        paren: expr.paren,
+        arguments: expr.arguments.into_iter().map(|x| folder.fold_expr(x)).collect::<Result<_, _>>()?,
+    }))
+}
+
+pub fn fold_get_expr<F: Fold + ?Sized>(folder: &mut F, expr: GetExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Get(GetExpr {
+        object: Box::new(folder.fold_expr(*expr.object)?),
+        name: expr.name,
+    }))
+}
+
+pub fn fold_grouping_expr<F: Fold + ?Sized>(folder: &mut F, expr: GroupingExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Grouping(GroupingExpr {
+        expression: Box::new(folder.fold_expr(*expr.expression)?),
+    }))
+}
+
+pub fn fold_interpolate_expr<F: Fold + ?Sized>(folder: &mut F, expr: InterpolateExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Interpolate(InterpolateExpr {
+        string: expr.string,
+        parts: expr.parts.into_iter().map(|x| folder.fold_expr(x)).collect::<Result<_, _>>()?,
+    }))
+}
+
+pub fn fold_index_expr<F: Fold + ?Sized>(folder: &mut F, expr: IndexExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Index(IndexExpr {
+        object: Box::new(folder.fold_expr(*expr.object)?),
+        bracket: expr.bracket,
+        index: Box::new(folder.fold_expr(*expr.index)?),
+    }))
+}
+
+pub fn fold_list_expr<F: Fold + ?Sized>(folder: &mut F, expr: ListExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::List(ListExpr {
+        bracket: expr.bracket,
+        elements: expr.elements.into_iter().map(|x| folder.fold_expr(x)).collect::<Result<_, _>>()?,
+    }))
+}
+
+pub fn fold_literal_expr<F: Fold + ?Sized>(_folder: &mut F, expr: LiteralExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Literal(LiteralExpr {
+        value: expr.value,
+    }))
+}
+
+pub fn fold_map_expr<F: Fold + ?Sized>(folder: &mut F, expr: MapExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Map(MapExpr {
+        brace: expr.brace,
+        keys: expr.keys.into_iter().map(|x| folder.fold_expr(x)).collect::<Result<_, _>>()?,
+        values: expr.values.into_iter().map(|x| folder.fold_expr(x)).collect::<Result<_, _>>()?,
+    }))
+}
+
+pub fn fold_set_expr<F: Fold + ?Sized>(folder: &mut F, expr: SetExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Set(SetExpr {
+        object: Box::new(folder.fold_expr(*expr.object)?),
+        name: expr.name,
+        value: Box::new(folder.fold_expr(*expr.value)?),
+    }))
+}
+
+pub fn fold_set_index_expr<F: Fold + ?Sized>(folder: &mut F, expr: SetIndexExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::SetIndex(SetIndexExpr {
+        object: Box::new(folder.fold_expr(*expr.object)?),
+        bracket: expr.bracket,
+        index: Box::new(folder.fold_expr(*expr.index)?),
+        value: Box::new(folder.fold_expr(*expr.value)?),
+    }))
+}
+
+pub fn fold_unary_expr<F: Fold + ?Sized>(folder: &mut F, expr: UnaryExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Unary(UnaryExpr {
+        operator: expr.operator,
+        right: Box::new(folder.fold_expr(*expr.right)?),
+    }))
+}
+
+pub fn fold_variable_expr<F: Fold + ?Sized>(_folder: &mut F, expr: VariableExpr) -> Result<Expr, LoxError> {
+    Ok(Expr::Variable(VariableExpr {
+        name: expr.name,
+    }))
+}
+
+pub trait Walk {
+    fn walk_expr(&mut self, expr: &Expr) -> Result<(), LoxError> {
+        walk_expr(self, expr)
+    }
+
+    fn walk_assign_expr(&mut self, expr: &AssignExpr) -> Result<(), LoxError> {
+        walk_assign_expr(self, expr)
+    }
+
+    fn walk_binary_expr(&mut self, expr: &BinaryExpr) -> Result<(), LoxError> {
+        walk_binary_expr(self, expr)
+    }
+
+    fn walk_call_expr(&mut self, expr: &CallExpr) -> Result<(), LoxError> {
+        walk_call_expr(self, expr)
+    }
+
+    fn walk_get_expr(&mut self, expr: &GetExpr) -> Result<(), LoxError> {
+        walk_get_expr(self, expr)
+    }
+
+    fn walk_grouping_expr(&mut self, expr: &GroupingExpr) -> Result<(), LoxError> {
+        walk_grouping_expr(self, expr)
+    }
+
+    fn walk_interpolate_expr(&mut self, expr: &InterpolateExpr) -> Result<(), LoxError> {
+        walk_interpolate_expr(self, expr)
+    }
+
+    fn walk_index_expr(&mut self, expr: &IndexExpr) -> Result<(), LoxError> {
+        walk_index_expr(self, expr)
+    }
+
+    fn walk_list_expr(&mut self, expr: &ListExpr) -> Result<(), LoxError> {
+        walk_list_expr(self, expr)
+    }
+
+    fn walk_literal_expr(&mut self, expr: &LiteralExpr) -> Result<(), LoxError> {
+        walk_literal_expr(self, expr)
+    }
+
+    fn walk_map_expr(&mut self, expr: &MapExpr) -> Result<(), LoxError> {
+        walk_map_expr(self, expr)
+    }
+
+    fn walk_set_expr(&mut self, expr: &SetExpr) -> Result<(), LoxError> {
+        walk_set_expr(self, expr)
+    }
+
+    fn walk_set_index_expr(&mut self, expr: &SetIndexExpr) -> Result<(), LoxError> {
+        walk_set_index_expr(self, expr)
+    }
+
+    fn walk_unary_expr(&mut self, expr: &UnaryExpr) -> Result<(), LoxError> {
+        walk_unary_expr(self, expr)
+    }
+
+    fn walk_variable_expr(&mut self, expr: &VariableExpr) -> Result<(), LoxError> {
+        walk_variable_expr(self, expr)
+    }
+}
+
+pub fn walk_expr<W: Walk + ?Sized>(walker: &mut W, expr: &Expr) -> Result<(), LoxError> {
+    match expr {
+        Expr::Assign(x) => walker.walk_assign_expr(x),
+        Expr::Binary(x) => walker.walk_binary_expr(x),
+        Expr::Call(x) => walker.walk_call_expr(x),
+        Expr::Get(x) => walker.walk_get_expr(x),
+        Expr::Grouping(x) => walker.walk_grouping_expr(x),
+        Expr::Interpolate(x) => walker.walk_interpolate_expr(x),
+        Expr::Index(x) => walker.walk_index_expr(x),
+        Expr::List(x) => walker.walk_list_expr(x),
+        Expr::Literal(x) => walker.walk_literal_expr(x),
+        Expr::Map(x) => walker.walk_map_expr(x),
+        Expr::Set(x) => walker.walk_set_expr(x),
+        Expr::SetIndex(x) => walker.walk_set_index_expr(x),
+        Expr::Unary(x) => walker.walk_unary_expr(x),
+        Expr::Variable(x) => walker.walk_variable_expr(x),
+    }
+}
+
+pub fn walk_assign_expr<W: Walk + ?Sized>(walker: &mut W, expr: &AssignExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.value)?;
+    Ok(())
+}
+
+pub fn walk_binary_expr<W: Walk + ?Sized>(walker: &mut W, expr: &BinaryExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.left)?;
+    walker.walk_expr(&expr.right)?;
+    Ok(())
+}
+
+pub fn walk_call_expr<W: Walk + ?Sized>(walker: &mut W, expr: &CallExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.callee)?;
+    for x in &expr.arguments {
+        walker.walk_expr(x)?;
+    }
+    Ok(())
+}
+
+pub fn walk_get_expr<W: Walk + ?Sized>(walker: &mut W, expr: &GetExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.object)?;
+    Ok(())
+}
+
+pub fn walk_grouping_expr<W: Walk + ?Sized>(walker: &mut W, expr: &GroupingExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.expression)?;
+    Ok(())
+}
+
+pub fn walk_interpolate_expr<W: Walk + ?Sized>(walker: &mut W, expr: &InterpolateExpr) -> Result<(), LoxError> {
+    for x in &expr.parts {
+        walker.walk_expr(x)?;
+    }
+    Ok(())
+}
+
+pub fn walk_index_expr<W: Walk + ?Sized>(walker: &mut W, expr: &IndexExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.object)?;
+    walker.walk_expr(&expr.index)?;
+    Ok(())
+}
+
+pub fn walk_list_expr<W: Walk + ?Sized>(walker: &mut W, expr: &ListExpr) -> Result<(), LoxError> {
+    for x in &expr.elements {
+        walker.walk_expr(x)?;
+    }
+    Ok(())
+}
+
+pub fn walk_literal_expr<W: Walk + ?Sized>(_walker: &mut W, _expr: &LiteralExpr) -> Result<(), LoxError> {
+    Ok(())
+}
+
+pub fn walk_map_expr<W: Walk + ?Sized>(walker: &mut W, expr: &MapExpr) -> Result<(), LoxError> {
+    for x in &expr.keys {
+        walker.walk_expr(x)?;
+    }
+    for x in &expr.values {
+        walker.walk_expr(x)?;
+    }
+    Ok(())
+}
+
+pub fn walk_set_expr<W: Walk + ?Sized>(walker: &mut W, expr: &SetExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.object)?;
+    walker.walk_expr(&expr.value)?;
+    Ok(())
+}
+
+pub fn walk_set_index_expr<W: Walk + ?Sized>(walker: &mut W, expr: &SetIndexExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.object)?;
+    walker.walk_expr(&expr.index)?;
+    walker.walk_expr(&expr.value)?;
+    Ok(())
+}
+
+pub fn walk_unary_expr<W: Walk + ?Sized>(walker: &mut W, expr: &UnaryExpr) -> Result<(), LoxError> {
+    walker.walk_expr(&expr.right)?;
+    Ok(())
+}
+
+pub fn walk_variable_expr<W: Walk + ?Sized>(_walker: &mut W, _expr: &VariableExpr) -> Result<(), LoxError> {
+    Ok(())
+}
+
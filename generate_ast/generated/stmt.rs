@@ -0,0 +1,196 @@
+use crate::error::*;
+use crate::expr::*;
+use crate::token::*;
+
+#[derive(Debug, PartialEq)]
+pub enum Stmt {
+    Block(BlockStmt),
+    Expression(ExpressionStmt),
+    FromImport(FromImportStmt),
+    Import(ImportStmt),
+    Print(PrintStmt),
+    Throw(ThrowStmt),
+    Try(TryStmt),
+    Var(VarStmt),
+}
+
+impl Stmt {
+    pub fn accept<T>(&self, stmt_visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        match self {
+            Stmt::Block(x) => x.accept(stmt_visitor),
+            Stmt::Expression(x) => x.accept(stmt_visitor),
+            Stmt::FromImport(x) => x.accept(stmt_visitor),
+            Stmt::Import(x) => x.accept(stmt_visitor),
+            Stmt::Print(x) => x.accept(stmt_visitor),
+            Stmt::Throw(x) => x.accept(stmt_visitor),
+            Stmt::Try(x) => x.accept(stmt_visitor),
+            Stmt::Var(x) => x.accept(stmt_visitor),
+        }
+    }
+
+    pub fn accept_mut<T>(&self, stmt_visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        match self {
+            Stmt::Block(x) => x.accept_mut(stmt_visitor),
+            Stmt::Expression(x) => x.accept_mut(stmt_visitor),
+            Stmt::FromImport(x) => x.accept_mut(stmt_visitor),
+            Stmt::Import(x) => x.accept_mut(stmt_visitor),
+            Stmt::Print(x) => x.accept_mut(stmt_visitor),
+            Stmt::Throw(x) => x.accept_mut(stmt_visitor),
+            Stmt::Try(x) => x.accept_mut(stmt_visitor),
+            Stmt::Var(x) => x.accept_mut(stmt_visitor),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BlockStmt {
+    pub statements: Vec<Stmt>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExpressionStmt {
+    pub expression: Expr,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FromImportStmt {
+    pub keyword: Token,
+    pub path: Token,
+    pub names: Vec<Token>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImportStmt {
+    pub keyword: Token,
+    pub path: Token,
+    pub name: Token,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PrintStmt {
+    pub expression: Expr,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ThrowStmt {
+    pub keyword: Token,
+    pub value: Expr,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TryStmt {
+    pub keyword: Token,
+    pub body: Vec<Stmt>,
+    pub name: Option<Token>,
+    pub handler: Vec<Stmt>,
+    pub finalizer: Option<Vec<Stmt>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VarStmt {
+    pub name: Token,
+    pub initializer: Option<Expr>,
+}
+
+pub trait StmtVisitor<T> {
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<T, LoxError>;
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<T, LoxError>;
+    fn visit_from_import_stmt(&self, stmt: &FromImportStmt) -> Result<T, LoxError>;
+    fn visit_import_stmt(&self, stmt: &ImportStmt) -> Result<T, LoxError>;
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<T, LoxError>;
+    fn visit_throw_stmt(&self, stmt: &ThrowStmt) -> Result<T, LoxError>;
+    fn visit_try_stmt(&self, stmt: &TryStmt) -> Result<T, LoxError>;
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<T, LoxError>;
+}
+
+pub trait StmtVisitorMut<T> {
+    fn visit_block_stmt(&mut self, stmt: &BlockStmt) -> Result<T, LoxError>;
+    fn visit_expression_stmt(&mut self, stmt: &ExpressionStmt) -> Result<T, LoxError>;
+    fn visit_from_import_stmt(&mut self, stmt: &FromImportStmt) -> Result<T, LoxError>;
+    fn visit_import_stmt(&mut self, stmt: &ImportStmt) -> Result<T, LoxError>;
+    fn visit_print_stmt(&mut self, stmt: &PrintStmt) -> Result<T, LoxError>;
+    fn visit_throw_stmt(&mut self, stmt: &ThrowStmt) -> Result<T, LoxError>;
+    fn visit_try_stmt(&mut self, stmt: &TryStmt) -> Result<T, LoxError>;
+    fn visit_var_stmt(&mut self, stmt: &VarStmt) -> Result<T, LoxError>;
+}
+
+impl BlockStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_block_stmt(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_block_stmt(self)
+    }
+}
+
+impl ExpressionStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_expression_stmt(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_expression_stmt(self)
+    }
+}
+
+impl FromImportStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_from_import_stmt(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_from_import_stmt(self)
+    }
+}
+
+impl ImportStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_import_stmt(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_import_stmt(self)
+    }
+}
+
+impl PrintStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_print_stmt(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_print_stmt(self)
+    }
+}
+
+impl ThrowStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_throw_stmt(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_throw_stmt(self)
+    }
+}
+
+impl TryStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_try_stmt(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_try_stmt(self)
+    }
+}
+
+impl VarStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_var_stmt(self)
+    }
+
+    pub fn accept_mut<T>(&self, visitor: &mut dyn StmtVisitorMut<T>) -> Result<T, LoxError> {
+        visitor.visit_var_stmt(self)
+    }
+}
+
@@ -1,48 +1,121 @@
-use std::fs::File;
+use std::fs;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
 struct TreeType {
     base_class_name: String,
     class_name: String,
-    fields: Vec<String>,
+    fields: Vec<Field>,
 }
 
-pub fn generate_ast(output_dir: &str) -> io::Result<()> {
-    define_ast(
-        output_dir,
-        "Expr",
-        &[
-            "Binary   : Box<Expr> left, Token operator, Box<Expr> right".to_string(),
-            "Grouping : Box<Expr> expression".to_string(),
-            "Literal  : Option<Object> value".to_string(),
-            "Unary    : Token operator, Box<Expr> right".to_string(),
-        ],
-    )?;
+#[derive(Debug)]
+struct Field {
+    name: String,
+    ftype: String,
+}
+
+struct Grammar {
+    base_name: &'static str,
+    // Whether to generate the Fold and Walk traits
+    traversals: bool,
+}
+
+// Each base production is described by `<base>.grammar` in the grammar
+// directory and rendered into `<base>.rs`.
+const GRAMMARS: &[Grammar] = &[
+    Grammar {
+        base_name: "Expr",
+        traversals: true,
+    },
+    Grammar {
+        base_name: "Stmt",
+        traversals: false,
+    },
+];
+
+pub fn grammar_files(grammar_dir: &Path) -> Vec<PathBuf> {
+    GRAMMARS
+        .iter()
+        .map(|g| grammar_dir.join(format!("{}.grammar", g.base_name.to_lowercase())))
+        .collect()
+}
+
+pub fn generate_ast(grammar_dir: &Path, output_dir: &Path) -> io::Result<()> {
+    for (name, source) in render_ast(grammar_dir)? {
+        let path = output_dir.join(name);
+        // Leave the file alone when nothing changed so its mtime stays put
+        if fs::read(&path).ok().as_deref() != Some(source.as_slice()) {
+            fs::write(path, source)?;
+        }
+    }
 
     Ok(())
 }
 
-fn define_ast(output_dir: &str, base_name: &str, types: &[String]) -> io::Result<()> {
-    let path = format!("{output_dir}/{}.rs", base_name.to_lowercase());
-    let mut file = File::create(path)?;
+// Returns the names of the files in `committed_dir` that differ from what the
+// grammar currently generates.
+pub fn check_ast(grammar_dir: &Path, committed_dir: &Path) -> io::Result<Vec<String>> {
+    let mut stale = Vec::new();
+    for (name, source) in render_ast(grammar_dir)? {
+        if fs::read(committed_dir.join(&name)).ok().as_deref() != Some(source.as_slice()) {
+            stale.push(name);
+        }
+    }
+
+    Ok(stale)
+}
+
+fn render_ast(grammar_dir: &Path) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut rendered = Vec::new();
+    for (grammar, path) in GRAMMARS.iter().zip(grammar_files(grammar_dir)) {
+        let source = fs::read_to_string(path)?;
+        let lines: Vec<&str> = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        // `use` lines are copied through as-is; the rest are productions
+        let (uses, types): (Vec<&str>, Vec<&str>) =
+            lines.into_iter().partition(|line| line.starts_with("use "));
+        rendered.push((
+            format!("{}.rs", grammar.base_name.to_lowercase()),
+            define_ast(grammar, &uses, &types)?,
+        ));
+    }
+
+    Ok(rendered)
+}
+
+fn define_ast(grammar: &Grammar, uses: &[&str], types: &[&str]) -> io::Result<Vec<u8>> {
+    let base_name = grammar.base_name;
+    let mut out = Vec::new();
     let mut tree_types = Vec::new();
 
     // use modules
-    writeln!(file, "use crate::error::*;")?;
-    writeln!(file, "use crate::object::*;")?;
-    writeln!(file, "use crate::token::*;")?;
-    writeln!(file)?;
+    writeln!(out, "use crate::error::*;")?;
+    for line in uses {
+        writeln!(out, "{line}")?;
+    }
+    writeln!(out)?;
 
     // parse the input strings
     for ntype in types {
-        let (base_class_name, args) = ntype.split_once(':').unwrap();
+        let (base_class_name, args) = ntype
+            .split_once(':')
+            .ok_or_else(|| bad_grammar(base_name, ntype))?;
         let class_name = format!("{}{}", base_class_name.trim(), base_name);
         let arg_split: Vec<&str> = args.trim().split(',').collect();
         let mut fields = Vec::new();
         for arg in arg_split {
-            let (t2type, name) = arg.trim().split_once(' ').unwrap();
-            fields.push(format!("{}: {}", name, t2type));
+            let (t2type, name) = arg
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| bad_grammar(base_name, ntype))?;
+            fields.push(Field {
+                name: name.to_string(),
+                ftype: t2type.to_string(),
+            });
         }
         tree_types.push(TreeType {
             base_class_name: base_class_name.trim().to_string(),
@@ -52,87 +125,301 @@ fn define_ast(output_dir: &str, base_name: &str, types: &[String]) -> io::Result
     }
 
     // enum for top-level production
-    writeln!(file, "pub enum {base_name} {{")?;
+    writeln!(out, "#[derive(Debug, PartialEq)]")?;
+    writeln!(out, "pub enum {base_name} {{")?;
     for t in &tree_types {
-        writeln!(file, "    {}({}),", t.base_class_name, t.class_name)?;
-    }
-    writeln!(file, "}}")?;
-    writeln!(file)?;
-
-    // TODO: generate accept on top-level production
-    // impl Expr {
-    //     pub fn accept<T>(&self, expr_visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
-    //         match self {
-    //             Expr::Binary(be) => be.accept(expr_visitor),
-    //             Expr::Grouping(ge) => ge.accept(expr_visitor),
-    //             Expr::Literal(le) => le.accept(expr_visitor),
-    //             Expr::Unary(ue) => ue.accept(expr_visitor),
-    //         }
-    //     }
-    // }
-    writeln!(file, "impl {} {{", base_name)?;
+        writeln!(out, "    {}({}),", t.base_class_name, t.class_name)?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    // accept and accept_mut on top-level production
+    writeln!(out, "impl {} {{", base_name)?;
     let param_name = format!("{}_visitor", base_name.to_lowercase());
     writeln!(
-        file,
+        out,
         "    pub fn accept<T>(&self, {}: &dyn {}Visitor<T>) -> Result<T, LoxError> {{",
         param_name, base_name
     )?;
-    writeln!(file, "        match self {{")?;
+    writeln!(out, "        match self {{")?;
     for t in &tree_types {
         writeln!(
-            file,
+            out,
             "            {}::{}(x) => x.accept({}),",
             base_name, t.base_class_name, param_name
         )?;
     }
-    writeln!(file, "        }}")?;
-    writeln!(file, "    }}")?;
-    writeln!(file, "}}")?;
-    writeln!(file)?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "    pub fn accept_mut<T>(&self, {}: &mut dyn {}VisitorMut<T>) -> Result<T, LoxError> {{",
+        param_name, base_name
+    )?;
+    writeln!(out, "        match self {{")?;
+    for t in &tree_types {
+        writeln!(
+            out,
+            "            {}::{}(x) => x.accept_mut({}),",
+            base_name, t.base_class_name, param_name
+        )?;
+    }
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
 
     // structs for each production rule
     for t in &tree_types {
-        writeln!(file, "pub struct {} {{", t.class_name)?;
+        writeln!(out, "#[derive(Debug, PartialEq)]")?;
+        writeln!(out, "pub struct {} {{", t.class_name)?;
         for f in t.fields.iter() {
-            writeln!(file, "    pub {},", f)?;
+            writeln!(out, "    pub {}: {},", f.name, f.ftype)?;
         }
-        writeln!(file, "}}")?;
-        writeln!(file)?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
     }
 
-    // trait for top-level visitor
-    writeln!(file, "pub trait {}Visitor<T> {{", base_name)?;
+    // traits for top-level visitors
+    define_visitor(&mut out, base_name, &tree_types, "Visitor", "&self")?;
+    define_visitor(&mut out, base_name, &tree_types, "VisitorMut", "&mut self")?;
+
+    // implementation of traits for each production rule
     for t in &tree_types {
+        writeln!(out, "impl {} {{", t.class_name)?;
+        writeln!(
+            out,
+            "    pub fn accept<T>(&self, visitor: &dyn {}Visitor<T>) -> Result<T, LoxError> {{",
+            base_name
+        )?;
         writeln!(
-            file,
-            "    fn visit_{}_{}(&self, {}: &{}) -> Result<T, LoxError>;",
-            t.base_class_name.to_lowercase(),
+            out,
+            "        visitor.visit_{}_{}(self)",
+            snake_case(&t.base_class_name),
+            base_name.to_lowercase()
+        )?;
+        writeln!(out, "    }}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    pub fn accept_mut<T>(&self, visitor: &mut dyn {}VisitorMut<T>) -> Result<T, LoxError> {{",
+            base_name
+        )?;
+        writeln!(
+            out,
+            "        visitor.visit_{}_{}(self)",
+            snake_case(&t.base_class_name),
+            base_name.to_lowercase()
+        )?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+    }
+
+    if grammar.traversals {
+        define_fold(&mut out, base_name, &tree_types)?;
+        define_walk(&mut out, base_name, &tree_types)?;
+    }
+
+    Ok(out)
+}
+
+fn define_visitor(
+    out: &mut Vec<u8>,
+    base_name: &str,
+    tree_types: &[TreeType],
+    suffix: &str,
+    receiver: &str,
+) -> io::Result<()> {
+    writeln!(out, "pub trait {}{}<T> {{", base_name, suffix)?;
+    for t in tree_types {
+        writeln!(
+            out,
+            "    fn visit_{}_{}({}, {}: &{}) -> Result<T, LoxError>;",
+            snake_case(&t.base_class_name),
             base_name.to_lowercase(),
+            receiver,
             base_name.to_lowercase(),
             t.class_name
         )?;
     }
-    writeln!(file, "}}")?;
-    writeln!(file)?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
 
-    // implementation of trait for each production rule
-    for t in &tree_types {
-        writeln!(file, "impl {} {{", t.class_name)?;
+    Ok(())
+}
+
+// Fold rebuilds the tree by value. Every method defaults to the free function
+// of the same name, so an override can still fall back to the stock rewrite.
+fn define_fold(out: &mut Vec<u8>, base_name: &str, tree_types: &[TreeType]) -> io::Result<()> {
+    let lower = base_name.to_lowercase();
+
+    writeln!(out, "pub trait Fold {{")?;
+    writeln!(
+        out,
+        "    fn fold_{lower}(&mut self, {lower}: {base_name}) -> Result<{base_name}, LoxError> {{"
+    )?;
+    writeln!(out, "        fold_{lower}(self, {lower})")?;
+    writeln!(out, "    }}")?;
+    for t in tree_types {
+        let method = format!("fold_{}_{lower}", snake_case(&t.base_class_name));
+        writeln!(out)?;
         writeln!(
-            file,
-            "    pub fn accept<T>(&self, visitor: &dyn {}Visitor<T>) -> Result<T, LoxError> {{",
-            base_name
+            out,
+            "    fn {method}(&mut self, {lower}: {}) -> Result<{base_name}, LoxError> {{",
+            t.class_name
         )?;
+        writeln!(out, "        {method}(self, {lower})")?;
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    writeln!(
+        out,
+        "pub fn fold_{lower}<F: Fold + ?Sized>(folder: &mut F, {lower}: {base_name}) -> Result<{base_name}, LoxError> {{"
+    )?;
+    writeln!(out, "    match {lower} {{")?;
+    for t in tree_types {
         writeln!(
-            file,
-            "        visitor.visit_{}_{}(self)",
-            t.base_class_name.to_lowercase(),
-            base_name.to_lowercase()
+            out,
+            "        {base_name}::{}(x) => folder.fold_{}_{lower}(x),",
+            t.base_class_name,
+            snake_case(&t.base_class_name)
+        )?;
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    for t in tree_types {
+        let recurses = t.fields.iter().any(|f| is_child(&f.ftype, base_name));
+        let folder = if recurses { "folder" } else { "_folder" };
+        writeln!(
+            out,
+            "pub fn fold_{}_{lower}<F: Fold + ?Sized>({folder}: &mut F, {lower}: {}) -> Result<{base_name}, LoxError> {{",
+            snake_case(&t.base_class_name),
+            t.class_name
+        )?;
+        writeln!(
+            out,
+            "    Ok({base_name}::{}({} {{",
+            t.base_class_name, t.class_name
+        )?;
+        for f in &t.fields {
+            let value = if f.ftype == format!("Box<{base_name}>") {
+                format!("Box::new(folder.fold_{lower}(*{lower}.{})?)", f.name)
+            } else if f.ftype == format!("Vec<{base_name}>") {
+                format!(
+                    "{lower}.{}.into_iter().map(|x| folder.fold_{lower}(x)).collect::<Result<_, _>>()?",
+                    f.name
+                )
+            } else {
+                format!("{lower}.{}", f.name)
+            };
+            writeln!(out, "        {}: {},", f.name, value)?;
+        }
+        writeln!(out, "    }}))")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+// Walk is the read-only counterpart of Fold: it visits every node in the tree
+// and does nothing by default.
+fn define_walk(out: &mut Vec<u8>, base_name: &str, tree_types: &[TreeType]) -> io::Result<()> {
+    let lower = base_name.to_lowercase();
+
+    writeln!(out, "pub trait Walk {{")?;
+    writeln!(
+        out,
+        "    fn walk_{lower}(&mut self, {lower}: &{base_name}) -> Result<(), LoxError> {{"
+    )?;
+    writeln!(out, "        walk_{lower}(self, {lower})")?;
+    writeln!(out, "    }}")?;
+    for t in tree_types {
+        let method = format!("walk_{}_{lower}", snake_case(&t.base_class_name));
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    fn {method}(&mut self, {lower}: &{}) -> Result<(), LoxError> {{",
+            t.class_name
         )?;
-        writeln!(file, "    }}")?;
-        writeln!(file, "}}")?;
-        writeln!(file)?;
+        writeln!(out, "        {method}(self, {lower})")?;
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    writeln!(
+        out,
+        "pub fn walk_{lower}<W: Walk + ?Sized>(walker: &mut W, {lower}: &{base_name}) -> Result<(), LoxError> {{"
+    )?;
+    writeln!(out, "    match {lower} {{")?;
+    for t in tree_types {
+        writeln!(
+            out,
+            "        {base_name}::{}(x) => walker.walk_{}_{lower}(x),",
+            t.base_class_name,
+            snake_case(&t.base_class_name)
+        )?;
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    for t in tree_types {
+        let recurses = t.fields.iter().any(|f| is_child(&f.ftype, base_name));
+        let (walker, param) = if recurses {
+            ("walker".to_string(), lower.clone())
+        } else {
+            ("_walker".to_string(), format!("_{lower}"))
+        };
+        writeln!(
+            out,
+            "pub fn walk_{}_{lower}<W: Walk + ?Sized>({walker}: &mut W, {param}: &{}) -> Result<(), LoxError> {{",
+            snake_case(&t.base_class_name),
+            t.class_name
+        )?;
+        for f in &t.fields {
+            if f.ftype == format!("Box<{base_name}>") {
+                writeln!(out, "    walker.walk_{lower}(&{lower}.{})?;", f.name)?;
+            } else if f.ftype == format!("Vec<{base_name}>") {
+                writeln!(out, "    for x in &{lower}.{} {{", f.name)?;
+                writeln!(out, "        walker.walk_{lower}(x)?;")?;
+                writeln!(out, "    }}")?;
+            }
+        }
+        writeln!(out, "    Ok(())")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
     }
 
     Ok(())
 }
+
+// Method names for a production, e.g. `visit_set_index_expr` for SetIndex
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+fn is_child(ftype: &str, base_name: &str) -> bool {
+    ftype == format!("Box<{base_name}>") || ftype == format!("Vec<{base_name}>")
+}
+
+fn bad_grammar(base_name: &str, line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("malformed {base_name} production: {line}"),
+    )
+}
@@ -1,5 +1,6 @@
 use crate::error::LoxError;
 use crate::expr::*;
+use crate::stmt::*;
 
 pub struct AstPrinter;
 
@@ -8,6 +9,10 @@ impl AstPrinter {
         expr.accept(self)
     }
 
+    pub fn print_stmt(&self, stmt: &Stmt) -> Result<String, LoxError> {
+        stmt.accept(self)
+    }
+
     fn parenthesize(&self, name: &str, exprs: &[&Expr]) -> Result<String, LoxError> {
         let mut builder = format!("({name}");
 
@@ -18,17 +23,107 @@ impl AstPrinter {
 
         Ok(builder)
     }
+
+    fn block(&self, name: &str, statements: &[Stmt]) -> Result<String, LoxError> {
+        let mut builder = format!("({name}");
+        for statement in statements {
+            builder = format!("{builder} {}", self.print_stmt(statement)?);
+        }
+        Ok(format!("{builder})"))
+    }
+}
+
+impl StmtVisitor<String> for AstPrinter {
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<String, LoxError> {
+        self.block("block", &stmt.statements)
+    }
+
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<String, LoxError> {
+        self.parenthesize(";", &[&stmt.expression])
+    }
+
+    fn visit_from_import_stmt(&self, stmt: &FromImportStmt) -> Result<String, LoxError> {
+        let names: Vec<&str> = stmt.names.iter().map(|name| name.lexeme.as_str()).collect();
+        Ok(format!(
+            "(from {} import {})",
+            stmt.path.lexeme,
+            names.join(" ")
+        ))
+    }
+
+    fn visit_import_stmt(&self, stmt: &ImportStmt) -> Result<String, LoxError> {
+        Ok(format!(
+            "(import {} as {})",
+            stmt.path.lexeme, stmt.name.lexeme
+        ))
+    }
+
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<String, LoxError> {
+        self.parenthesize("print", &[&stmt.expression])
+    }
+
+    fn visit_throw_stmt(&self, stmt: &ThrowStmt) -> Result<String, LoxError> {
+        self.parenthesize("throw", &[&stmt.value])
+    }
+
+    fn visit_try_stmt(&self, stmt: &TryStmt) -> Result<String, LoxError> {
+        let mut builder = format!("(try {}", self.block("block", &stmt.body)?);
+        if let Some(name) = &stmt.name {
+            let handler = self.block(&format!("catch {}", name.lexeme), &stmt.handler)?;
+            builder = format!("{builder} {handler}");
+        }
+        if let Some(finalizer) = &stmt.finalizer {
+            builder = format!("{builder} {}", self.block("finally", finalizer)?);
+        }
+        Ok(format!("{builder})"))
+    }
+
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<String, LoxError> {
+        let name = format!("var {}", stmt.name.lexeme);
+        match &stmt.initializer {
+            Some(initializer) => self.parenthesize(&name, &[initializer]),
+            None => self.parenthesize(&name, &[]),
+        }
+    }
 }
 
 impl ExprVisitor<String> for AstPrinter {
+    fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<String, LoxError> {
+        self.parenthesize(&format!("{}=", expr.name.lexeme), &[&expr.value])
+    }
+
     fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<String, LoxError> {
         self.parenthesize(&expr.operator.lexeme, &[&expr.left, &expr.right])
     }
 
+    fn visit_call_expr(&self, expr: &CallExpr) -> Result<String, LoxError> {
+        let mut exprs = vec![expr.callee.as_ref()];
+        exprs.extend(&expr.arguments);
+        self.parenthesize("call", &exprs)
+    }
+
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<String, LoxError> {
+        self.parenthesize(&format!(".{}", expr.name.lexeme), &[&expr.object])
+    }
+
     fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<String, LoxError> {
         self.parenthesize("group", &[&expr.expression])
     }
 
+    fn visit_index_expr(&self, expr: &IndexExpr) -> Result<String, LoxError> {
+        self.parenthesize("[]", &[&expr.object, &expr.index])
+    }
+
+    fn visit_interpolate_expr(&self, expr: &InterpolateExpr) -> Result<String, LoxError> {
+        let parts: Vec<&Expr> = expr.parts.iter().collect();
+        self.parenthesize("interpolate", &parts)
+    }
+
+    fn visit_list_expr(&self, expr: &ListExpr) -> Result<String, LoxError> {
+        let elements: Vec<&Expr> = expr.elements.iter().collect();
+        self.parenthesize("list", &elements)
+    }
+
     fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<String, LoxError> {
         if let Some(value) = &expr.value {
             Ok(value.to_string())
@@ -37,9 +132,34 @@ impl ExprVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_map_expr(&self, expr: &MapExpr) -> Result<String, LoxError> {
+        let entries: Vec<&Expr> = expr
+            .keys
+            .iter()
+            .zip(&expr.values)
+            .flat_map(|(key, value)| [key, value])
+            .collect();
+        self.parenthesize("map", &entries)
+    }
+
+    fn visit_set_expr(&self, expr: &SetExpr) -> Result<String, LoxError> {
+        self.parenthesize(
+            &format!(".{}=", expr.name.lexeme),
+            &[&expr.object, &expr.value],
+        )
+    }
+
+    fn visit_set_index_expr(&self, expr: &SetIndexExpr) -> Result<String, LoxError> {
+        self.parenthesize("[]=", &[&expr.object, &expr.index, &expr.value])
+    }
+
     fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<String, LoxError> {
         self.parenthesize(&expr.operator.lexeme, &[&expr.right])
     }
+
+    fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<String, LoxError> {
+        Ok(expr.name.lexeme.clone())
+    }
 }
 
 pub struct RpnPrinter;
@@ -62,14 +182,42 @@ impl RpnPrinter {
 }
 
 impl ExprVisitor<String> for RpnPrinter {
+    fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<String, LoxError> {
+        self.parenthesize(&format!("{}=", expr.name.lexeme), &[&expr.value])
+    }
+
     fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<String, LoxError> {
         self.parenthesize(&expr.operator.lexeme, &[&expr.left, &expr.right])
     }
 
+    fn visit_call_expr(&self, expr: &CallExpr) -> Result<String, LoxError> {
+        let mut exprs = vec![expr.callee.as_ref()];
+        exprs.extend(&expr.arguments);
+        self.parenthesize("call", &exprs)
+    }
+
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<String, LoxError> {
+        self.parenthesize(&format!(".{}", expr.name.lexeme), &[&expr.object])
+    }
+
     fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<String, LoxError> {
         self.parenthesize("group", &[&expr.expression])
     }
 
+    fn visit_index_expr(&self, expr: &IndexExpr) -> Result<String, LoxError> {
+        self.parenthesize("[]", &[&expr.object, &expr.index])
+    }
+
+    fn visit_interpolate_expr(&self, expr: &InterpolateExpr) -> Result<String, LoxError> {
+        let parts: Vec<&Expr> = expr.parts.iter().collect();
+        self.parenthesize("interpolate", &parts)
+    }
+
+    fn visit_list_expr(&self, expr: &ListExpr) -> Result<String, LoxError> {
+        let elements: Vec<&Expr> = expr.elements.iter().collect();
+        self.parenthesize("list", &elements)
+    }
+
     fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<String, LoxError> {
         if let Some(value) = &expr.value {
             Ok(value.to_string())
@@ -78,7 +226,103 @@ impl ExprVisitor<String> for RpnPrinter {
         }
     }
 
+    fn visit_map_expr(&self, expr: &MapExpr) -> Result<String, LoxError> {
+        let entries: Vec<&Expr> = expr
+            .keys
+            .iter()
+            .zip(&expr.values)
+            .flat_map(|(key, value)| [key, value])
+            .collect();
+        self.parenthesize("map", &entries)
+    }
+
+    fn visit_set_expr(&self, expr: &SetExpr) -> Result<String, LoxError> {
+        self.parenthesize(
+            &format!(".{}=", expr.name.lexeme),
+            &[&expr.object, &expr.value],
+        )
+    }
+
+    fn visit_set_index_expr(&self, expr: &SetIndexExpr) -> Result<String, LoxError> {
+        self.parenthesize("[]=", &[&expr.object, &expr.index, &expr.value])
+    }
+
     fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<String, LoxError> {
         self.parenthesize(&expr.operator.lexeme, &[&expr.right])
     }
+
+    fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<String, LoxError> {
+        Ok(expr.name.lexeme.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::Object;
+    use crate::token::Token;
+    use crate::token_type::TokenType;
+
+    fn literal(value: bool) -> Box<Expr> {
+        Box::new(Expr::Literal(LiteralExpr {
+            value: Some(Object::Bool(value)),
+        }))
+    }
+
+    fn group(expression: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::Grouping(GroupingExpr { expression }))
+    }
+
+    fn token(ttype: TokenType, lexeme: &str) -> Token {
+        Token::new(ttype, lexeme.to_string(), None, 1)
+    }
+
+    // !(true == (false))
+    fn sample() -> Expr {
+        Expr::Unary(UnaryExpr {
+            operator: token(TokenType::Bang, "!"),
+            right: group(Box::new(Expr::Binary(BinaryExpr {
+                left: literal(true),
+                operator: token(TokenType::EqualEqual, "=="),
+                right: group(literal(false)),
+            }))),
+        })
+    }
+
+    // Only looks at literals; the default walk reaches them
+    struct Literals(Vec<Object>);
+
+    impl Walk for Literals {
+        fn walk_literal_expr(&mut self, expr: &LiteralExpr) -> Result<(), LoxError> {
+            self.0.extend(expr.value.clone());
+            Ok(())
+        }
+    }
+
+    // Replaces each grouping with what it contains
+    struct Ungroup;
+
+    impl Fold for Ungroup {
+        fn fold_grouping_expr(&mut self, expr: GroupingExpr) -> Result<Expr, LoxError> {
+            self.fold_expr(*expr.expression)
+        }
+    }
+
+    #[test]
+    fn walk_falls_back_to_the_default() {
+        let mut literals = Literals(Vec::new());
+        literals.walk_expr(&sample()).unwrap();
+        assert_eq!(literals.0, [Object::Bool(true), Object::Bool(false)]);
+    }
+
+    #[test]
+    fn fold_rewrites_a_subtree() {
+        let printer = AstPrinter;
+        assert_eq!(
+            printer.print(&sample()).unwrap(),
+            "(! (group (== true (group false))))"
+        );
+        let folded = Ungroup.fold_expr(sample()).unwrap();
+        assert_eq!(printer.print(&folded).unwrap(), "(! (== true false))");
+    }
 }
@@ -0,0 +1,84 @@
+use crate::convert::IntoLox;
+use crate::environment::Environment;
+use crate::error::LoxError;
+use crate::interpreter::Dialect;
+use crate::math;
+use crate::native::NativeFunction;
+use crate::object::Object;
+use crate::system::{self, System};
+use num_bigint::BigInt;
+use std::cell::Cell;
+use std::rc::Rc;
+
+// The global functions every session starts with, and the `Math` namespace.
+// `str` formats values the way `print` does in the current dialect, so it
+// shares the interpreter's, and the I/O functions share its `System`. The
+// script and its modules share them too, so assigning to one shadows it.
+pub(crate) fn globals(dialect: &Rc<Cell<Dialect>>, system: &Rc<System>) -> Environment {
+    let mut globals = Environment::shared();
+    let mut define = |function: NativeFunction| {
+        let name = function.name().to_string();
+        globals.define(&name, function.into());
+    };
+
+    let dialect = Rc::clone(dialect);
+    define(NativeFunction::new("str", 1, move |args| {
+        Ok(dialect.get().to_text(&args[0]).into_lox())
+    }));
+    define(NativeFunction::new("num", 1, |args| match &args[0] {
+        Object::Str(s) => Ok(parse_number(s).unwrap_or(Object::Nil)),
+        value if value.as_f64().is_some() => Ok(value.clone()),
+        value => Err(LoxError::host_error(&format!(
+            "Expected a string but got {}.",
+            value.type_name()
+        ))),
+    }));
+    define(NativeFunction::new("type", 1, |args| {
+        Ok(args[0].type_name().into_lox())
+    }));
+    for function in system::natives(system) {
+        define(function);
+    }
+    globals.define("Math", math::namespace());
+    globals
+}
+
+// Reads a number the way it would be written in Lox, allowing a sign and
+// surrounding whitespace; None if the text isn't a number
+fn parse_number(text: &str) -> Option<Object> {
+    let text = text.trim();
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (digits, None),
+    };
+    let is_digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    if !is_digits(whole) || !fraction.is_none_or(is_digits) {
+        return None;
+    }
+    match fraction {
+        Some(_) => text.parse().ok().map(Object::Num),
+        None => match text.parse::<i64>() {
+            Ok(n) => Some(Object::Int(n)),
+            Err(_) => text.parse::<BigInt>().ok().map(Object::BigInt),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(parse_number(" 42 "), Some(Object::Int(42)));
+        assert_eq!(parse_number("-1.5"), Some(Object::Num(-1.5)));
+        assert!(matches!(
+            parse_number("123456789012345678901234567890"),
+            Some(Object::BigInt(_))
+        ));
+        for text in ["", "-", "1.", ".5", "1e5", "inf", "NaN", "12abc"] {
+            assert_eq!(parse_number(text), None, "{text}");
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::error::LoxError;
+use crate::list::List;
+use crate::object::Object;
+
+// Conversions between Lox values and Rust types, for native functions:
+//
+//   lox.register_fn("hypot", 2, |args| {
+//       let (x, y) = (f64::from_lox(&args[0])?, f64::from_lox(&args[1])?);
+//       Ok(x.hypot(y).into_lox())
+//   });
+//
+// A value of the wrong type is a runtime error at the call.
+pub trait FromLox: Sized {
+    fn from_lox(value: &Object) -> Result<Self, LoxError>;
+}
+
+pub trait IntoLox {
+    fn into_lox(self) -> Object;
+}
+
+fn expected(what: &str, value: &Object) -> LoxError {
+    LoxError::host_error(&format!("Expected {what} but got {}.", value.type_name()))
+}
+
+impl FromLox for Object {
+    fn from_lox(value: &Object) -> Result<Object, LoxError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromLox for f64 {
+    fn from_lox(value: &Object) -> Result<f64, LoxError> {
+        value.as_f64().ok_or_else(|| expected("a number", value))
+    }
+}
+
+impl FromLox for i64 {
+    fn from_lox(value: &Object) -> Result<i64, LoxError> {
+        match value {
+            Object::Int(n) => Ok(*n),
+            Object::BigInt(_) => Err(LoxError::host_error("Integer is too large.")),
+            _ => Err(expected("an integer", value)),
+        }
+    }
+}
+
+impl FromLox for String {
+    fn from_lox(value: &Object) -> Result<String, LoxError> {
+        match value {
+            Object::Str(s) => Ok(s.clone()),
+            _ => Err(expected("a string", value)),
+        }
+    }
+}
+
+impl FromLox for bool {
+    fn from_lox(value: &Object) -> Result<bool, LoxError> {
+        match value {
+            Object::Bool(b) => Ok(*b),
+            _ => Err(expected("a bool", value)),
+        }
+    }
+}
+
+// nil is None
+impl<T: FromLox> FromLox for Option<T> {
+    fn from_lox(value: &Object) -> Result<Option<T>, LoxError> {
+        match value {
+            Object::Nil => Ok(None),
+            _ => T::from_lox(value).map(Some),
+        }
+    }
+}
+
+// Copies the elements out of a list
+impl<T: FromLox> FromLox for Vec<T> {
+    fn from_lox(value: &Object) -> Result<Vec<T>, LoxError> {
+        match value {
+            Object::List(list) => list.elements().iter().map(T::from_lox).collect(),
+            _ => Err(expected("a list", value)),
+        }
+    }
+}
+
+impl IntoLox for Object {
+    fn into_lox(self) -> Object {
+        self
+    }
+}
+
+impl IntoLox for f64 {
+    fn into_lox(self) -> Object {
+        Object::Num(self)
+    }
+}
+
+impl IntoLox for i64 {
+    fn into_lox(self) -> Object {
+        Object::Int(self)
+    }
+}
+
+impl IntoLox for String {
+    fn into_lox(self) -> Object {
+        Object::Str(self)
+    }
+}
+
+impl IntoLox for &str {
+    fn into_lox(self) -> Object {
+        Object::Str(self.to_string())
+    }
+}
+
+impl IntoLox for bool {
+    fn into_lox(self) -> Object {
+        Object::Bool(self)
+    }
+}
+
+impl IntoLox for () {
+    fn into_lox(self) -> Object {
+        Object::Nil
+    }
+}
+
+impl<T: IntoLox> IntoLox for Option<T> {
+    fn into_lox(self) -> Object {
+        match self {
+            Some(value) => value.into_lox(),
+            None => Object::Nil,
+        }
+    }
+}
+
+impl<T: IntoLox> IntoLox for Vec<T> {
+    fn into_lox(self) -> Object {
+        List::new(self.into_iter().map(IntoLox::into_lox).collect()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        assert_eq!(f64::from_lox(&1.5.into_lox()).unwrap(), 1.5);
+        assert_eq!(f64::from_lox(&Object::Int(2)).unwrap(), 2.0);
+        assert_eq!(String::from_lox(&"abc".into_lox()).unwrap(), "abc");
+        assert!(bool::from_lox(&true.into_lox()).unwrap());
+        assert_eq!(Option::<i64>::from_lox(&Object::Nil).unwrap(), None);
+        assert_eq!(Some(3i64).into_lox(), Object::Int(3));
+        let list = vec![1i64, 2].into_lox();
+        assert_eq!(Vec::<i64>::from_lox(&list).unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn wrong_types_are_errors() {
+        let err = f64::from_lox(&Object::Str("x".to_string())).unwrap_err();
+        assert_eq!(err.message(), "Expected a number but got string.");
+        assert!(i64::from_lox(&Object::Num(1.0)).is_err());
+    }
+}
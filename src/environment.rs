@@ -0,0 +1,76 @@
+use crate::error::LoxError;
+use crate::object::Object;
+use crate::token::Token;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// One scope of variables. Lookups that miss here continue in the enclosing
+// scope, ending at the built-ins, which have none.
+#[derive(Default)]
+pub struct Environment {
+    values: HashMap<String, Object>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+    // Assigning to a name found in a shared scope defines it in the scope
+    // below instead, so one module can't replace it for the others
+    shared: bool,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::default()
+    }
+
+    pub fn new_enclosing(enclosing: Rc<RefCell<Environment>>) -> Environment {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+            shared: false,
+        }
+    }
+
+    pub fn shared() -> Environment {
+        Environment {
+            shared: true,
+            ..Environment::default()
+        }
+    }
+
+    // Redefining a name in the same scope replaces it
+    pub fn define(&mut self, name: &str, value: Object) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Object, LoxError> {
+        match (self.values.get(&name.lexeme), &self.enclosing) {
+            (Some(value), _) => Ok(value.clone()),
+            (None, Some(enclosing)) => enclosing.borrow().get(name),
+            (None, None) => Err(Environment::undefined(name)),
+        }
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Object) -> Result<(), LoxError> {
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            *slot = value;
+            return Ok(());
+        }
+        match &self.enclosing {
+            Some(enclosing) if enclosing.borrow().shared => {
+                enclosing.borrow().get(name)?;
+                self.values.insert(name.lexeme.clone(), value);
+                Ok(())
+            }
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(Environment::undefined(name)),
+        }
+    }
+
+    // The variables defined in this scope itself, in no particular order
+    pub fn values(&self) -> impl Iterator<Item = (&String, &Object)> {
+        self.values.iter()
+    }
+
+    fn undefined(name: &Token) -> LoxError {
+        LoxError::runtime_error(name, &format!("Undefined variable '{}'.", name.lexeme))
+    }
+}
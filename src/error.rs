@@ -1,53 +1,272 @@
+use crate::object::Object;
 use crate::token::Token;
 use crate::token_type::TokenType;
+use std::fmt;
+use std::io::{self, Write};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ErrorKind {
+    // Found while scanning or parsing, before anything runs
+    Compile,
+    Runtime,
+    // A script couldn't be read
+    Io,
+    // The script called `exit` with this status. It unwinds like an error so
+    // the host decides what ending the script means.
+    Exit(i32),
+}
+
+#[derive(Debug, Clone)]
 pub struct LoxError {
-    token: Option<Token>,
+    kind: ErrorKind,
+    // Boxed to keep the Result types that carry errors small
+    token: Option<Box<Token>>,
     line: usize,
     message: String,
+    // Where a runtime error unwound through, innermost first, with the line
+    // each frame was called or imported from
+    trace: Vec<(Frame, usize)>,
+    // The value a script threw; None for errors the interpreter raised
+    value: Option<Object>,
+}
+
+// A function call, shown as `f()`, or a module, shown as its path
+#[derive(Debug, Clone)]
+enum Frame {
+    Call(String),
+    Module(String),
+}
+
+impl Frame {
+    fn name(&self) -> &str {
+        match self {
+            Frame::Call(name) | Frame::Module(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Frame::Call(function) => write!(f, "{function}()"),
+            Frame::Module(path) => write!(f, "{path}"),
+        }
+    }
 }
 
+// Errors are reported by whoever decides the error won't be recovered from,
+// so constructing one has no side effects.
 impl LoxError {
     pub fn error(line: usize, message: &str) -> LoxError {
-        let err = LoxError {
+        LoxError {
+            kind: ErrorKind::Compile,
             token: None,
             line,
             message: message.to_string(),
-        };
-        err.report("");
-        err
+            trace: Vec::new(),
+            value: None,
+        }
     }
 
     pub fn parse_error(token: &Token, message: &str) -> LoxError {
-        let err = LoxError {
-            token: Some(token.clone()),
+        LoxError {
+            kind: ErrorKind::Compile,
+            token: Some(Box::new(token.clone())),
             line: token.line,
             message: message.to_string(),
-        };
-        err.report("");
-        err
+            trace: Vec::new(),
+            value: None,
+        }
     }
 
     pub fn runtime_error(token: &Token, message: &str) -> LoxError {
-        let err = LoxError {
-            token: Some(token.clone()),
+        LoxError {
+            kind: ErrorKind::Runtime,
+            token: Some(Box::new(token.clone())),
             line: token.line,
             message: message.to_string(),
-        };
-        err.report("");
-        err
+            trace: Vec::new(),
+            value: None,
+        }
+    }
+
+    // Raised by host code such as a native function, which has no token to
+    // point at; the interpreter places it at the call that failed.
+    pub fn host_error(message: &str) -> LoxError {
+        LoxError {
+            kind: ErrorKind::Runtime,
+            token: None,
+            line: 0,
+            message: message.to_string(),
+            trace: Vec::new(),
+            value: None,
+        }
+    }
+
+    pub fn exit(status: i32) -> LoxError {
+        LoxError {
+            kind: ErrorKind::Exit(status),
+            token: None,
+            line: 0,
+            message: format!("Exited with status {status}."),
+            trace: Vec::new(),
+            value: None,
+        }
+    }
+
+    // `throw value;`, described by `message` in case nothing catches it
+    pub fn thrown(token: &Token, value: Object, message: &str) -> LoxError {
+        LoxError {
+            value: Some(value),
+            ..LoxError::runtime_error(token, message)
+        }
+    }
+
+    pub(crate) fn or_at(self, token: &Token) -> LoxError {
+        match self.token {
+            Some(_) => self,
+            None => LoxError {
+                token: Some(Box::new(token.clone())),
+                line: token.line,
+                ..self
+            },
+        }
+    }
+
+    // Records that the error unwound out of `function`, called on `line`
+    pub(crate) fn in_call(mut self, function: &str, line: usize) -> LoxError {
+        self.trace.push((Frame::Call(function.to_string()), line));
+        self
+    }
+
+    // Records that the error unwound out of the module imported on `line`
+    pub(crate) fn in_module(mut self, module: &str, line: usize) -> LoxError {
+        self.trace.push((Frame::Module(module.to_string()), line));
+        self
     }
 
-    pub fn report(&self, loc: &str) {
-        if let Some(token) = &self.token {
-            if token.is(TokenType::Eof) {
-                eprintln!("{} at end {}", token.line, self.message);
-            } else {
-                eprintln!("{} at '{}' {}", token.line, token.lexeme, self.message);
+    pub fn io_error(path: &str, err: &io::Error) -> LoxError {
+        LoxError {
+            kind: ErrorKind::Io,
+            token: None,
+            line: 0,
+            message: format!("Could not read {path}: {err}"),
+            trace: Vec::new(),
+            value: None,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    // The names of the functions and modules the error unwound through,
+    // innermost first
+    pub fn trace(&self) -> impl Iterator<Item = &str> {
+        self.trace.iter().map(|(frame, _)| frame.name())
+    }
+
+    pub fn value(&self) -> Option<&Object> {
+        self.value.as_ref()
+    }
+
+    pub fn report(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{self}")
+    }
+}
+
+// Uses the same layout as the reference implementation so that its test
+// suite's expectations apply unchanged.
+impl fmt::Display for LoxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.kind, &self.token) {
+            (ErrorKind::Runtime, _) if self.trace.is_empty() => {
+                write!(f, "{}\n[line {}]", self.message, self.line)
+            }
+            // Each frame is shown with the line it had reached, which for its
+            // caller is the line of the call
+            (ErrorKind::Runtime, _) => {
+                write!(f, "{}", self.message)?;
+                let mut line = self.line;
+                for (frame, call_line) in &self.trace {
+                    write!(f, "\n[line {line}] in {frame}")?;
+                    line = *call_line;
+                }
+                write!(f, "\n[line {line}] in script")
+            }
+            (ErrorKind::Compile, Some(token)) if token.is(TokenType::Eof) => {
+                write!(f, "[line {}] Error at end: {}", self.line, self.message)
+            }
+            (ErrorKind::Compile, Some(token)) => write!(
+                f,
+                "[line {}] Error at '{}': {}",
+                self.line, token.lexeme, self.message
+            ),
+            (ErrorKind::Compile, None) => {
+                write!(f, "[line {}] Error: {}", self.line, self.message)
             }
+            (ErrorKind::Io | ErrorKind::Exit(_), _) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for LoxError {}
+
+// A failed operation on values, before it has been tied to the token that
+// caused it. The message uses the reference Lox wording, and the operand types
+// are appended when it is displayed.
+#[derive(Debug)]
+pub struct RuntimeError {
+    message: String,
+    operand_types: Vec<&'static str>,
+}
+
+impl RuntimeError {
+    pub fn new(message: &str) -> RuntimeError {
+        RuntimeError {
+            message: message.to_string(),
+            operand_types: Vec::new(),
+        }
+    }
+
+    pub fn operand(message: &str, operand: &Object) -> RuntimeError {
+        RuntimeError {
+            message: message.to_string(),
+            operand_types: vec![operand.type_name()],
+        }
+    }
+
+    pub fn operands(message: &str, left: &Object, right: &Object) -> RuntimeError {
+        RuntimeError {
+            message: message.to_string(),
+            operand_types: vec![left.type_name(), right.type_name()],
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.operand_types.is_empty() {
+            write!(f, "{}", self.message)
         } else {
-            eprintln!("[line {}] Error{}: {}", self.line, loc, self.message);
+            write!(
+                f,
+                "{} Got {}.",
+                self.message,
+                self.operand_types.join(" and ")
+            )
         }
     }
 }
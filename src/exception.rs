@@ -0,0 +1,35 @@
+use crate::convert::IntoLox;
+use crate::error::LoxError;
+use crate::interpreter::Dialect;
+use crate::object::Object;
+use crate::userdata::{UserClass, UserData};
+
+// What `catch` binds when the interpreter raised the error rather than a
+// `throw`: an `Error` instance with the message and line it was reported with
+struct Caught {
+    message: String,
+    line: usize,
+}
+
+pub(crate) fn error_object(err: &LoxError) -> Object {
+    let class = UserClass::builder::<Caught>("Error")
+        .getter("message", |caught| caught.message.as_str().into_lox())
+        .getter("line", |caught| Object::Int(caught.line as i64))
+        .build();
+    let caught = Caught {
+        message: err.message().to_string(),
+        line: err.line(),
+    };
+    UserData::new(&class, caught).into()
+}
+
+// How a thrown value is reported if nothing catches it. Rethrowing a caught
+// error keeps its message.
+pub(crate) fn describe(value: &Object, dialect: Dialect) -> String {
+    if let Object::UserData(instance) = value {
+        if let Some(caught) = instance.borrow::<Caught>() {
+            return caught.message.clone();
+        }
+    }
+    format!("Uncaught exception: {}", dialect.to_text(value))
+}
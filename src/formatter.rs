@@ -0,0 +1,267 @@
+use crate::error::LoxError;
+use crate::expr::*;
+use crate::object::Object;
+use crate::stmt::*;
+
+// Prints a tree back out as Lox source, one statement per line. Parentheses
+// only appear where the tree has a grouping, so parsing the output gives back
+// the same tree.
+pub struct Formatter;
+
+impl Formatter {
+    pub fn format(&self, statements: &[Stmt]) -> Result<String, LoxError> {
+        let mut builder = String::new();
+        self.write_statements(statements, 0, &mut builder)?;
+        Ok(builder)
+    }
+
+    // Blocks are laid out here rather than by indenting their formatted
+    // text, which would also indent the lines of multi-line strings.
+    fn write_statements(
+        &self,
+        statements: &[Stmt],
+        depth: usize,
+        builder: &mut String,
+    ) -> Result<(), LoxError> {
+        let indent = "    ".repeat(depth);
+        for stmt in statements {
+            builder.push_str(&indent);
+            match stmt {
+                Stmt::Block(block) => self.write_block(&block.statements, depth, builder)?,
+                Stmt::Try(stmt) => self.write_try(stmt, depth, builder)?,
+                _ => builder.push_str(&stmt.accept(self)?),
+            }
+            builder.push('\n');
+        }
+        Ok(())
+    }
+
+    // `{`, the statements one level in, and `}` back at `depth`
+    fn write_block(
+        &self,
+        statements: &[Stmt],
+        depth: usize,
+        builder: &mut String,
+    ) -> Result<(), LoxError> {
+        builder.push_str("{\n");
+        self.write_statements(statements, depth + 1, builder)?;
+        builder.push_str(&"    ".repeat(depth));
+        builder.push('}');
+        Ok(())
+    }
+
+    fn write_try(
+        &self,
+        stmt: &TryStmt,
+        depth: usize,
+        builder: &mut String,
+    ) -> Result<(), LoxError> {
+        builder.push_str("try ");
+        self.write_block(&stmt.body, depth, builder)?;
+        if let Some(name) = &stmt.name {
+            builder.push_str(&format!(" catch ({}) ", name.lexeme));
+            self.write_block(&stmt.handler, depth, builder)?;
+        }
+        if let Some(finalizer) = &stmt.finalizer {
+            builder.push_str(" finally ");
+            self.write_block(finalizer, depth, builder)?;
+        }
+        Ok(())
+    }
+
+    pub fn format_expr(&self, expr: &Expr) -> Result<String, LoxError> {
+        expr.accept(self)
+    }
+
+    fn literal(value: &Object) -> String {
+        match value {
+            Object::Str(s) => format!("\"{s}\""),
+            Object::Nil => "nil".to_string(),
+            // Keep the fractional part so the literal still scans as a float
+            Object::Num(n) if n.is_finite() && n.fract() == 0.0 => format!("{n:.1}"),
+            _ => value.to_string(),
+        }
+    }
+}
+
+impl StmtVisitor<String> for Formatter {
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<String, LoxError> {
+        let mut builder = "{\n".to_string();
+        self.write_statements(&stmt.statements, 1, &mut builder)?;
+        builder.push('}');
+        Ok(builder)
+    }
+
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<String, LoxError> {
+        Ok(format!("{};", self.format_expr(&stmt.expression)?))
+    }
+
+    fn visit_from_import_stmt(&self, stmt: &FromImportStmt) -> Result<String, LoxError> {
+        let names: Vec<&str> = stmt.names.iter().map(|name| name.lexeme.as_str()).collect();
+        Ok(format!(
+            "from {} import {};",
+            stmt.path.lexeme,
+            names.join(", ")
+        ))
+    }
+
+    fn visit_import_stmt(&self, stmt: &ImportStmt) -> Result<String, LoxError> {
+        Ok(format!(
+            "import {} as {};",
+            stmt.path.lexeme, stmt.name.lexeme
+        ))
+    }
+
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<String, LoxError> {
+        Ok(format!("print {};", self.format_expr(&stmt.expression)?))
+    }
+
+    fn visit_throw_stmt(&self, stmt: &ThrowStmt) -> Result<String, LoxError> {
+        Ok(format!("throw {};", self.format_expr(&stmt.value)?))
+    }
+
+    fn visit_try_stmt(&self, stmt: &TryStmt) -> Result<String, LoxError> {
+        let mut builder = String::new();
+        self.write_try(stmt, 0, &mut builder)?;
+        Ok(builder)
+    }
+
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<String, LoxError> {
+        match &stmt.initializer {
+            Some(initializer) => Ok(format!(
+                "var {} = {};",
+                stmt.name.lexeme,
+                self.format_expr(initializer)?
+            )),
+            None => Ok(format!("var {};", stmt.name.lexeme)),
+        }
+    }
+}
+
+impl ExprVisitor<String> for Formatter {
+    fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<String, LoxError> {
+        Ok(format!(
+            "{} = {}",
+            expr.name.lexeme,
+            self.format_expr(&expr.value)?
+        ))
+    }
+
+    fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<String, LoxError> {
+        Ok(format!(
+            "{} {} {}",
+            self.format_expr(&expr.left)?,
+            expr.operator.lexeme,
+            self.format_expr(&expr.right)?
+        ))
+    }
+
+    fn visit_call_expr(&self, expr: &CallExpr) -> Result<String, LoxError> {
+        let arguments = expr
+            .arguments
+            .iter()
+            .map(|argument| self.format_expr(argument))
+            .collect::<Result<Vec<String>, LoxError>>()?;
+        Ok(format!(
+            "{}({})",
+            self.format_expr(&expr.callee)?,
+            arguments.join(", ")
+        ))
+    }
+
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<String, LoxError> {
+        Ok(format!(
+            "{}.{}",
+            self.format_expr(&expr.object)?,
+            expr.name.lexeme
+        ))
+    }
+
+    fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<String, LoxError> {
+        Ok(format!("({})", self.format_expr(&expr.expression)?))
+    }
+
+    fn visit_index_expr(&self, expr: &IndexExpr) -> Result<String, LoxError> {
+        Ok(format!(
+            "{}[{}]",
+            self.format_expr(&expr.object)?,
+            self.format_expr(&expr.index)?
+        ))
+    }
+
+    fn visit_interpolate_expr(&self, expr: &InterpolateExpr) -> Result<String, LoxError> {
+        let mut builder = "\"".to_string();
+        for (i, part) in expr.parts.iter().enumerate() {
+            match part {
+                Expr::Literal(LiteralExpr {
+                    value: Some(Object::Str(text)),
+                }) if i % 2 == 0 => builder.push_str(text),
+                _ => builder.push_str(&format!("${{{}}}", self.format_expr(part)?)),
+            }
+        }
+        builder.push('"');
+        Ok(builder)
+    }
+
+    fn visit_list_expr(&self, expr: &ListExpr) -> Result<String, LoxError> {
+        let elements = expr
+            .elements
+            .iter()
+            .map(|element| self.format_expr(element))
+            .collect::<Result<Vec<String>, LoxError>>()?;
+        Ok(format!("[{}]", elements.join(", ")))
+    }
+
+    fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<String, LoxError> {
+        match &expr.value {
+            Some(value) => Ok(Formatter::literal(value)),
+            None => Ok("nil".to_string()),
+        }
+    }
+
+    fn visit_map_expr(&self, expr: &MapExpr) -> Result<String, LoxError> {
+        let entries = expr
+            .keys
+            .iter()
+            .zip(&expr.values)
+            .map(|(key, value)| {
+                Ok(format!(
+                    "{}: {}",
+                    self.format_expr(key)?,
+                    self.format_expr(value)?
+                ))
+            })
+            .collect::<Result<Vec<String>, LoxError>>()?;
+        Ok(format!("{{{}}}", entries.join(", ")))
+    }
+
+    fn visit_set_expr(&self, expr: &SetExpr) -> Result<String, LoxError> {
+        Ok(format!(
+            "{}.{} = {}",
+            self.format_expr(&expr.object)?,
+            expr.name.lexeme,
+            self.format_expr(&expr.value)?
+        ))
+    }
+
+    fn visit_set_index_expr(&self, expr: &SetIndexExpr) -> Result<String, LoxError> {
+        Ok(format!(
+            "{}[{}] = {}",
+            self.format_expr(&expr.object)?,
+            self.format_expr(&expr.index)?,
+            self.format_expr(&expr.value)?
+        ))
+    }
+
+    fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<String, LoxError> {
+        Ok(format!(
+            "{}{}",
+            expr.operator.lexeme,
+            self.format_expr(&expr.right)?
+        ))
+    }
+
+    fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<String, LoxError> {
+        Ok(expr.name.lexeme.clone())
+    }
+}
@@ -1,89 +1,624 @@
-use crate::error::LoxError;
+use crate::builtins;
+use crate::environment::Environment;
+use crate::error::{ErrorKind, LoxError, RuntimeError};
+use crate::exception;
 use crate::expr::*;
+use crate::list::List;
+use crate::map::Map;
+use crate::module::Module;
 use crate::object::Object;
+use crate::optimizer::ConstantFolder;
+use crate::parser::Parser;
+use crate::scanner::Scanner;
+use crate::stmt::*;
+use crate::strings;
+use crate::system::System;
 use crate::token::*;
 use crate::token_type::TokenType;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering::{self, *};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
 
-pub struct Interpreter;
+// How floating point division by zero and NaN results are treated. Integer
+// division by zero is always an error, since there is no infinity to return.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum NumericPolicy {
+    // Follow IEEE 754: `1/0` is `inf` and `0/0` is `NaN`
+    #[default]
+    Ieee,
+    // Any division by zero is a runtime error
+    ErrorOnZeroDivision,
+    // Only operations that would produce NaN are runtime errors
+    ErrorOnNan,
+}
+
+// The strict dialect behaves exactly like the reference jlox/clox
+// implementations: only numbers can be ordered, values print the way the
+// reference `print` shows them, and error messages leave out operand types.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum Dialect {
+    #[default]
+    Extended,
+    Strict,
+}
+
+impl Dialect {
+    // How `print` shows a value
+    pub fn stringify(self, value: &Object) -> String {
+        if self == Dialect::Extended {
+            return value.to_string();
+        }
+        match value {
+            Object::Str(s) => s.clone(),
+            Object::Nil => "nil".to_string(),
+            Object::Num(n) if n.is_infinite() => {
+                let sign = if n.is_sign_negative() { "-" } else { "" };
+                format!("{sign}Infinity")
+            }
+            Object::List(list) => list.show(|element| self.element(element)),
+            Object::Map(map) => map.show(|element| self.element(element)),
+            _ => value.to_string(),
+        }
+    }
+
+    // How a value is shown inside a list or map: strings keep their quotes so that
+    // `["a, b"]` can't be mistaken for two elements
+    fn element(self, value: &Object) -> String {
+        match value {
+            Object::Str(_) => value.to_string(),
+            _ => self.stringify(value),
+        }
+    }
+
+    // How a value reads as part of some text, e.g. interpolated into a
+    // string: a string is its contents and anything else is shown the way
+    // `print` shows it
+    pub fn to_text(self, value: &Object) -> String {
+        match value {
+            Object::Str(s) => s.clone(),
+            _ => self.stringify(value),
+        }
+    }
+}
+
+// What a script may do outside the interpreter. Nothing is allowed by
+// default, so an untrusted script can't touch files or the environment.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct Capabilities {
+    // `readLine` and `readFile`
+    pub read: bool,
+    // `writeFile`
+    pub write: bool,
+    // `env`
+    pub env: bool,
+}
+
+impl Capabilities {
+    pub fn all() -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            env: true,
+        }
+    }
+}
+
+// `print` writes to the output and errors are reported to the error output;
+// they are stdout and stderr unless the host swaps them, e.g. to capture them
+// in a SharedBuffer.
+pub struct Interpreter {
+    numeric_policy: NumericPolicy,
+    // Shared with the built-in functions that format values
+    dialect: Rc<Cell<Dialect>>,
+    // The built-ins and whatever the host defined, enclosing the globals of
+    // the script and of every module
+    builtins: Rc<RefCell<Environment>>,
+    globals: Rc<RefCell<Environment>>,
+    // The innermost scope of whatever is running
+    environment: RefCell<Rc<RefCell<Environment>>>,
+    output: RefCell<Box<dyn Write>>,
+    error_output: RefCell<Box<dyn Write>>,
+    // The input, arguments and capabilities, shared with the natives that
+    // use them
+    system: Rc<System>,
+    // Where imports are looked for after the importing file's directory
+    module_path: Vec<PathBuf>,
+    // Every module that has been imported, by its canonical path
+    modules: RefCell<HashMap<PathBuf, Rc<Module>>>,
+    // The files whose code is running, innermost last, by canonical path and
+    // the name they were given
+    files: RefCell<Vec<(PathBuf, String)>>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        let dialect = Rc::new(Cell::new(Dialect::default()));
+        let system = Rc::new(System::default());
+        let builtins = Rc::new(RefCell::new(builtins::globals(&dialect, &system)));
+        let globals = Rc::new(RefCell::new(Environment::new_enclosing(Rc::clone(
+            &builtins,
+        ))));
+        Interpreter {
+            numeric_policy: NumericPolicy::default(),
+            dialect,
+            builtins,
+            environment: RefCell::new(Rc::clone(&globals)),
+            globals,
+            output: RefCell::new(Box::new(io::stdout())),
+            error_output: RefCell::new(Box::new(io::stderr())),
+            system,
+            module_path: Vec::new(),
+            modules: RefCell::new(HashMap::new()),
+            files: RefCell::new(Vec::new()),
+        }
+    }
+}
 
 impl Interpreter {
-    fn evaluate(&self, expr: &Expr) -> Result<Object, LoxError> {
+    pub fn new() -> Interpreter {
+        Interpreter::default()
+    }
+
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = RefCell::new(output);
+    }
+
+    pub fn set_error_output(&mut self, error_output: Box<dyn Write>) {
+        self.error_output = RefCell::new(error_output);
+    }
+
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        *self.system.input.borrow_mut() = input;
+    }
+
+    // Failing to write a report leaves nowhere to report that to. A script
+    // that exits hasn't failed, so there's nothing to report then.
+    pub fn report(&self, err: &LoxError) {
+        if let ErrorKind::Exit(_) = err.kind() {
+            return;
+        }
+        let _ = err.report(&mut *self.error_output.borrow_mut());
+    }
+
+    // Returns None at the end of the input; the line ending is dropped
+    pub fn read_line(&self) -> io::Result<Option<String>> {
+        self.system.read_line()
+    }
+
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.system.capabilities.set(capabilities);
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.system.capabilities.get()
+    }
+
+    // What `args()` returns to scripts
+    pub fn set_args(&mut self, args: Vec<String>) {
+        *self.system.args.borrow_mut() = args;
+    }
+
+    pub fn set_module_path(&mut self, module_path: Vec<PathBuf>) {
+        self.module_path = module_path;
+    }
+
+    // Runs `run` as the code of the file at `path`, so that its imports are
+    // found relative to it
+    pub fn in_file<T>(&self, path: &Path, run: impl FnOnce() -> T) -> T {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let name = path.display().to_string();
+        self.files.borrow_mut().push((canonical, name));
+        let result = run();
+        self.files.borrow_mut().pop();
+        result
+    }
+
+    pub fn set_numeric_policy(&mut self, policy: NumericPolicy) {
+        self.numeric_policy = policy;
+    }
+
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect.set(dialect);
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.dialect.get()
+    }
+
+    // Defined beside the built-ins, so modules see it too
+    pub fn define(&self, name: &str, value: Object) {
+        self.builtins.borrow_mut().define(name, value);
+    }
+
+    // Global variables sorted by name, including the built-ins the script
+    // hasn't shadowed
+    pub fn globals(&self) -> Vec<(String, Object)> {
+        let mut globals: HashMap<String, Object> = HashMap::new();
+        for scope in [&self.builtins, &self.globals] {
+            for (name, value) in scope.borrow().values() {
+                globals.insert(name.clone(), value.clone());
+            }
+        }
+        let mut globals: Vec<(String, Object)> = globals.into_iter().collect();
+        globals.sort_by(|(a, _), (b, _)| a.cmp(b));
+        globals
+    }
+
+    // Forgets every global, including any the host defined, leaving only the
+    // built-in functions; modules will be run again when they're next imported
+    pub fn reset(&self) {
+        *self.builtins.borrow_mut() = builtins::globals(&self.dialect, &self.system);
+        *self.globals.borrow_mut() = Environment::new_enclosing(Rc::clone(&self.builtins));
+        self.environment.replace(Rc::clone(&self.globals));
+        self.modules.borrow_mut().clear();
+    }
+
+    pub fn evaluate(&self, expr: &Expr) -> Result<Object, LoxError> {
         expr.accept(self)
     }
 
-    pub fn interpret(&self, expr: &Expr) -> Result<(), ()> {
-        match self.evaluate(expr) {
-            Ok(value) => {
-                println!("{}", value);
-                Ok(())
+    pub fn stringify(&self, value: &Object) -> String {
+        self.dialect().stringify(value)
+    }
+
+    pub fn to_text(&self, value: &Object) -> String {
+        self.dialect().to_text(value)
+    }
+
+    fn runtime_error(&self, operator: &Token, err: RuntimeError) -> LoxError {
+        match self.dialect() {
+            Dialect::Extended => LoxError::runtime_error(operator, &err.to_string()),
+            Dialect::Strict => LoxError::runtime_error(operator, err.message()),
+        }
+    }
+
+    fn execute(&self, stmt: &Stmt) -> Result<(), LoxError> {
+        stmt.accept(self)
+    }
+
+    // The enclosing scope is restored even when a statement fails
+    fn execute_block(&self, statements: &[Stmt], environment: Environment) -> Result<(), LoxError> {
+        let previous = self.environment.replace(Rc::new(RefCell::new(environment)));
+        let result = statements.iter().try_for_each(|stmt| self.execute(stmt));
+        self.environment.replace(previous);
+        result
+    }
+
+    // A module runs once, the first time it's imported, and later imports
+    // share the same module
+    fn import(&self, path: &Token) -> Result<Rc<Module>, LoxError> {
+        let name = match &path.literal {
+            Some(Object::Str(name)) => name.clone(),
+            _ => path.lexeme.trim_matches('"').to_string(),
+        };
+        let error = |message: String| LoxError::runtime_error(path, &message);
+        let denied = || {
+            error(format!(
+                "Importing \"{name}\" is not allowed; run with --allow-read to allow it."
+            ))
+        };
+        // Without --allow-read a module has to be a file under the directory
+        // it's found in, so absolute paths, `..` and links out are refused
+        let confined = !self.capabilities().read;
+        let relative = Path::new(&name)
+            .components()
+            .all(|part| matches!(part, Component::Normal(_) | Component::CurDir));
+        if confined && !relative {
+            return Err(denied());
+        }
+        let (dir, file) = self
+            .resolve(&name)
+            .ok_or_else(|| error(format!("Could not find module \"{name}\".")))?;
+        let canonical = file.canonicalize().unwrap_or_else(|_| file.clone());
+        if confined {
+            // An empty directory is the current one
+            let dir = Path::new(".").join(dir).canonicalize();
+            if !dir.is_ok_and(|dir| canonical.starts_with(dir)) {
+                return Err(denied());
+            }
+        }
+        if let Some(module) = self.modules.borrow().get(&canonical) {
+            return Ok(Rc::clone(module));
+        }
+        let files = self.files.borrow();
+        if let Some(start) = files.iter().position(|(running, _)| *running == canonical) {
+            let chain: Vec<&str> = files[start..]
+                .iter()
+                .map(|(_, running)| running.as_str())
+                .chain([name.as_str()])
+                .collect();
+            return Err(error(format!("Import cycle: {}.", chain.join(" -> "))));
+        }
+        drop(files);
+
+        let source = std::fs::read_to_string(&file)
+            .map_err(|err| error(format!("Could not read {}: {err}.", file.display())))?;
+        // A compile error, like the ones it reports, so `catch` doesn't stop it
+        let statements = self.compile(&source).ok_or_else(|| {
+            LoxError::error(path.line, &format!("Could not compile module \"{name}\"."))
+        })?;
+
+        // Modules share the built-ins but not the globals of whoever imports
+        // them
+        let globals = Rc::new(RefCell::new(Environment::new_enclosing(Rc::clone(
+            &self.builtins,
+        ))));
+        self.files
+            .borrow_mut()
+            .push((canonical.clone(), name.clone()));
+        let previous = self.environment.replace(Rc::clone(&globals));
+        let result = self.interpret(&statements);
+        self.environment.replace(previous);
+        self.files.borrow_mut().pop();
+        result.map_err(|err| err.in_module(&name, path.line))?;
+
+        let module = Rc::new(Module::new(&name, globals.borrow().values()));
+        self.modules
+            .borrow_mut()
+            .insert(canonical, Rc::clone(&module));
+        Ok(module)
+    }
+
+    // Looks beside the importing file first (or in the current directory
+    // outside any file), then along the module path. Returns the directory
+    // the module was found in along with its file.
+    fn resolve(&self, name: &str) -> Option<(PathBuf, PathBuf)> {
+        let beside = self
+            .files
+            .borrow()
+            .last()
+            .and_then(|(file, _)| file.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        std::iter::once(beside)
+            .chain(self.module_path.iter().cloned())
+            .map(|dir| {
+                let file = dir.join(name);
+                (dir, file)
+            })
+            .find(|(_, file)| file.is_file())
+    }
+
+    // Reports every error in a module's source; None if there were any
+    fn compile(&self, source: &str) -> Option<Vec<Stmt>> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.set_dialect(self.dialect());
+        let scanned = scanner.scan_tokens().cloned();
+        scanner.errors().iter().for_each(|err| self.report(err));
+        let tokens = scanned.ok()?;
+        let mut parser = Parser::new(&tokens);
+        let parsed = parser.parse();
+        parser.errors().iter().for_each(|err| self.report(err));
+        ConstantFolder::new(self)
+            .optimize(parsed.ok()?)
+            .inspect_err(|err| self.report(err))
+            .ok()
+    }
+
+    // Stops at the first runtime error and hands it back for reporting
+    pub fn interpret(&self, statements: &[Stmt]) -> Result<(), LoxError> {
+        for statement in statements {
+            self.execute(statement)?;
+        }
+        Ok(())
+    }
+}
+
+impl Interpreter {
+    fn binary(&self, left: Object, op: TokenType, right: Object) -> Result<Object, RuntimeError> {
+        let divides = matches!(
+            op,
+            TokenType::Slash | TokenType::TildeSlash | TokenType::Percent
+        );
+        if divides
+            && self.numeric_policy == NumericPolicy::ErrorOnZeroDivision
+            && left.as_f64().is_some()
+            && right.as_f64() == Some(0.0)
+        {
+            return Err(RuntimeError::new("Division by zero."));
+        }
+
+        let result = match op {
+            TokenType::Plus => left + right,
+            TokenType::Minus => left - right,
+            TokenType::Star => left * right,
+            TokenType::Slash => left / right,
+            TokenType::TildeSlash => left.floor_div(right),
+            TokenType::Percent => left % right,
+            TokenType::Greater => Ok(Object::Bool(self.compare(&left, &right)? == Some(Greater))),
+            TokenType::GreaterEqual => Ok(Object::Bool(matches!(
+                self.compare(&left, &right)?,
+                Some(Greater | Equal)
+            ))),
+            TokenType::Less => Ok(Object::Bool(self.compare(&left, &right)? == Some(Less))),
+            TokenType::LessEqual => Ok(Object::Bool(matches!(
+                self.compare(&left, &right)?,
+                Some(Less | Equal)
+            ))),
+            TokenType::EqualEqual => Ok(Object::Bool(left == right)),
+            TokenType::BangEqual => Ok(Object::Bool(left != right)),
+            _ => Err(RuntimeError::new("unhandled binary operator")),
+        }?;
+
+        match result {
+            Object::Num(n) if n.is_nan() && self.numeric_policy == NumericPolicy::ErrorOnNan => {
+                Err(RuntimeError::new("Result is not a number."))
             }
-            Err(err) => {
-                err.report("");
-                Err(())
+            result => Ok(result),
+        }
+    }
+
+    fn compare(&self, left: &Object, right: &Object) -> Result<Option<Ordering>, RuntimeError> {
+        if self.dialect() == Dialect::Strict
+            && (left.as_f64().is_none() || right.as_f64().is_none())
+        {
+            return Err(RuntimeError::operands(
+                "Operands must be numbers.",
+                left,
+                right,
+            ));
+        }
+        left.compare(right)
+    }
+}
+
+impl StmtVisitor<()> for Interpreter {
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<(), LoxError> {
+        let enclosing = Rc::clone(&self.environment.borrow());
+        self.execute_block(&stmt.statements, Environment::new_enclosing(enclosing))
+    }
+
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<(), LoxError> {
+        self.evaluate(&stmt.expression)?;
+        Ok(())
+    }
+
+    fn visit_from_import_stmt(&self, stmt: &FromImportStmt) -> Result<(), LoxError> {
+        let module = self.import(&stmt.path)?;
+        for name in &stmt.names {
+            let value = module.get(&name.lexeme).map_err(|err| err.or_at(name))?;
+            self.environment
+                .borrow()
+                .borrow_mut()
+                .define(&name.lexeme, value);
+        }
+        Ok(())
+    }
+
+    fn visit_import_stmt(&self, stmt: &ImportStmt) -> Result<(), LoxError> {
+        let module = self.import(&stmt.path)?;
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .define(&stmt.name.lexeme, Object::Module(module));
+        Ok(())
+    }
+
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<(), LoxError> {
+        let value = self.evaluate(&stmt.expression)?;
+        writeln!(self.output.borrow_mut(), "{}", self.stringify(&value))
+            .map_err(|err| LoxError::host_error(&format!("Could not write output: {err}")))
+    }
+
+    fn visit_throw_stmt(&self, stmt: &ThrowStmt) -> Result<(), LoxError> {
+        let value = self.evaluate(&stmt.value)?;
+        let message = exception::describe(&value, self.dialect());
+        Err(LoxError::thrown(&stmt.keyword, value, &message))
+    }
+
+    // Only runtime errors are caught; a script calling `exit` still runs the
+    // `finally` clauses on its way out
+    fn visit_try_stmt(&self, stmt: &TryStmt) -> Result<(), LoxError> {
+        let scope = || Environment::new_enclosing(Rc::clone(&self.environment.borrow()));
+        let mut result = self.execute_block(&stmt.body, scope());
+
+        let caught = match (&result, &stmt.name) {
+            (Err(err), Some(name)) if err.kind() == ErrorKind::Runtime => {
+                let value = err
+                    .value()
+                    .cloned()
+                    .unwrap_or_else(|| exception::error_object(err));
+                Some((name, value))
             }
+            _ => None,
+        };
+        if let Some((name, value)) = caught {
+            let mut handler_scope = scope();
+            handler_scope.define(&name.lexeme, value);
+            result = self.execute_block(&stmt.handler, handler_scope);
         }
+
+        if let Some(finalizer) = &stmt.finalizer {
+            self.execute_block(finalizer, scope())?;
+        }
+        result
+    }
+
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<(), LoxError> {
+        let value = match &stmt.initializer {
+            Some(initializer) => self.evaluate(initializer)?,
+            None => Object::Nil,
+        };
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .define(&stmt.name.lexeme, value);
+        Ok(())
     }
 }
 
 impl ExprVisitor<Object> for Interpreter {
+    fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<Object, LoxError> {
+        let value = self.evaluate(&expr.value)?;
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .assign(&expr.name, value.clone())?;
+        Ok(value)
+    }
+
     fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<Object, LoxError> {
         let left = self.evaluate(&expr.left)?;
         let right = self.evaluate(&expr.right)?;
-        let op = expr.operator.token_type();
-
-        // break out the cases where left and right are the same flavor
-        let result: Object = match (left, right) {
-            (Object::Num(left), Object::Num(right)) => match op {
-                TokenType::Plus => Object::Num(left + right),
-                TokenType::Minus => Object::Num(left - right),
-                TokenType::Star => Object::Num(left * right),
-                TokenType::Slash => Object::Num(left / right),
-                TokenType::Greater => Object::Bool(left > right),
-                TokenType::GreaterEqual => Object::Bool(left >= right),
-                TokenType::Less => Object::Bool(left < right),
-                TokenType::LessEqual => Object::Bool(left <= right),
-                TokenType::EqualEqual => Object::Bool(left == right),
-                TokenType::BangEqual => Object::Bool(left != right),
-                _ => Object::ArithmeticError,
-            },
-            (Object::Str(left), Object::Str(right)) => match op {
-                TokenType::Plus => Object::Str(format!("{}{}", left, right)),
-                TokenType::Greater => Object::Bool(left > right),
-                TokenType::GreaterEqual => Object::Bool(left >= right),
-                TokenType::Less => Object::Bool(left < right),
-                TokenType::LessEqual => Object::Bool(left <= right),
-                TokenType::EqualEqual => Object::Bool(left == right),
-                TokenType::BangEqual => Object::Bool(left != right),
-                _ => Object::ArithmeticError,
-            },
-            (Object::Bool(left), Object::Bool(right)) => match op {
-                TokenType::EqualEqual => Object::Bool(left == right),
-                TokenType::BangEqual => Object::Bool(left != right),
-                _ => Object::ArithmeticError,
-            },
-            (Object::Nil, Object::Nil) => match op {
-                TokenType::EqualEqual => Object::Bool(true),
-                TokenType::BangEqual => Object::Bool(false),
-                _ => Object::ArithmeticError,
-            },
-            (Object::Nil, _) => match op {
-                TokenType::EqualEqual => Object::Bool(false),
-                TokenType::BangEqual => Object::Bool(true),
-                _ => Object::ArithmeticError,
-            },
-            (_, _) => match op {
-                TokenType::EqualEqual => Object::Bool(false),
-                TokenType::BangEqual => Object::Bool(true),
-                _ => Object::ArithmeticError,
-            },
+
+        self.binary(left, expr.operator.token_type(), right)
+            .map_err(|err| self.runtime_error(&expr.operator, err))
+    }
+
+    fn visit_call_expr(&self, expr: &CallExpr) -> Result<Object, LoxError> {
+        let callee = self.evaluate(&expr.callee)?;
+        let arguments = expr
+            .arguments
+            .iter()
+            .map(|argument| self.evaluate(argument))
+            .collect::<Result<Vec<Object>, LoxError>>()?;
+
+        let Object::NativeFunction(function) = callee else {
+            return Err(LoxError::runtime_error(
+                &expr.paren,
+                "Can only call functions and classes.",
+            ));
         };
+        if arguments.len() != function.arity() {
+            return Err(LoxError::runtime_error(
+                &expr.paren,
+                &format!(
+                    "Expected {} arguments but got {}.",
+                    function.arity(),
+                    arguments.len()
+                ),
+            ));
+        }
+        function.call(&arguments).map_err(|err| {
+            err.or_at(&expr.paren)
+                .in_call(function.name(), expr.paren.line)
+        })
+    }
 
-        match result {
-            Object::ArithmeticError => Err(LoxError::runtime_error(
-                &expr.operator,
-                "Invalid Arithmetic Expression",
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<Object, LoxError> {
+        match self.evaluate(&expr.object)? {
+            Object::UserData(instance) => instance
+                .get(&expr.name.lexeme)
+                .map_err(|err| err.or_at(&expr.name)),
+            Object::List(list) => list
+                .method(&expr.name.lexeme)
+                .map_err(|err| err.or_at(&expr.name)),
+            Object::Map(map) => map
+                .method(&expr.name.lexeme)
+                .map_err(|err| err.or_at(&expr.name)),
+            Object::Module(module) => module
+                .get(&expr.name.lexeme)
+                .map_err(|err| err.or_at(&expr.name)),
+            // The reference implementation has no methods on strings
+            Object::Str(s) if self.dialect() == Dialect::Extended => {
+                strings::method(&s, &expr.name.lexeme).map_err(|err| err.or_at(&expr.name))
+            }
+            _ => Err(LoxError::runtime_error(
+                &expr.name,
+                "Only instances have properties.",
             )),
-            a => Ok(a),
         }
     }
 
@@ -91,8 +626,47 @@ impl ExprVisitor<Object> for Interpreter {
         self.evaluate(&expr.expression)
     }
 
+    fn visit_index_expr(&self, expr: &IndexExpr) -> Result<Object, LoxError> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        let value = match object {
+            Object::List(list) => list.get(&index),
+            Object::Map(map) => map.get(&index),
+            _ => {
+                return Err(LoxError::runtime_error(
+                    &expr.bracket,
+                    "Only lists and maps can be indexed.",
+                ))
+            }
+        };
+        value.map_err(|err| err.or_at(&expr.bracket))
+    }
+
+    fn visit_interpolate_expr(&self, expr: &InterpolateExpr) -> Result<Object, LoxError> {
+        let mut text = String::new();
+        for part in &expr.parts {
+            text.push_str(&self.to_text(&self.evaluate(part)?));
+        }
+        Ok(Object::Str(text))
+    }
+
+    fn visit_list_expr(&self, expr: &ListExpr) -> Result<Object, LoxError> {
+        let elements = expr
+            .elements
+            .iter()
+            .map(|element| self.evaluate(element))
+            .collect::<Result<Vec<Object>, LoxError>>()?;
+        Ok(List::new(elements).into())
+    }
+
     fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<Object, LoxError> {
         match &expr.value {
+            // The reference implementation only has floating point numbers
+            Some(value @ (Object::Int(_) | Object::BigInt(_)))
+                if self.dialect() == Dialect::Strict =>
+            {
+                Ok(Object::Num(value.as_f64().unwrap_or(f64::NAN)))
+            }
             Some(value) => Ok(value.clone()),
             _ => Err(LoxError::runtime_error(
                 &Token {
@@ -106,35 +680,74 @@ impl ExprVisitor<Object> for Interpreter {
         }
     }
 
+    // Keys and values are evaluated in source order, and a repeated key keeps
+    // its first position but takes its last value
+    fn visit_map_expr(&self, expr: &MapExpr) -> Result<Object, LoxError> {
+        let map = Map::default();
+        for (key, value) in expr.keys.iter().zip(&expr.values) {
+            let key = self.evaluate(key)?;
+            let value = self.evaluate(value)?;
+            map.set(&key, value).map_err(|err| err.or_at(&expr.brace))?;
+        }
+        Ok(map.into())
+    }
+
+    fn visit_set_expr(&self, expr: &SetExpr) -> Result<Object, LoxError> {
+        let Object::UserData(instance) = self.evaluate(&expr.object)? else {
+            return Err(LoxError::runtime_error(
+                &expr.name,
+                "Only instances have fields.",
+            ));
+        };
+        let value = self.evaluate(&expr.value)?;
+        instance
+            .set(&expr.name.lexeme, value.clone())
+            .map_err(|err| err.or_at(&expr.name))?;
+        Ok(value)
+    }
+
+    fn visit_set_index_expr(&self, expr: &SetIndexExpr) -> Result<Object, LoxError> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        let value = self.evaluate(&expr.value)?;
+        let stored = match object {
+            Object::List(list) => list.set(&index, value.clone()),
+            Object::Map(map) => map.set(&index, value.clone()),
+            _ => {
+                return Err(LoxError::runtime_error(
+                    &expr.bracket,
+                    "Only lists and maps can be indexed.",
+                ))
+            }
+        };
+        stored.map_err(|err| err.or_at(&expr.bracket))?;
+        Ok(value)
+    }
+
     fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<Object, LoxError> {
         let right = self.evaluate(&expr.right)?;
 
-        let result: Result<Object, LoxError> = match expr.operator.token_type() {
-            TokenType::Minus => Ok(-right),
+        let result = match expr.operator.token_type() {
+            TokenType::Minus => -right,
             TokenType::Bang => Ok(!right),
-            _ => Err(LoxError::runtime_error(
-                &expr.operator,
-                "unhandled unary operator",
-            )),
+            _ => Err(RuntimeError::new("unhandled unary operator")),
         };
 
-        if matches!(result, Ok(Object::ArithmeticError)) {
-            Err(LoxError::runtime_error(
-                &expr.operator,
-                "Invalid Arithmetic Expression",
-            ))
-        } else {
-            result
-        }
+        result.map_err(|err| self.runtime_error(&expr.operator, err))
+    }
+
+    fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<Object, LoxError> {
+        self.environment.borrow().borrow().get(&expr.name)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use num_bigint::BigInt;
 
     fn invoke_unary(operator: Token, right: Object) -> Result<Object, LoxError> {
-        let terp = Interpreter {};
+        let terp = Interpreter::new();
         let unary_expr = UnaryExpr {
             operator,
             right: Box::new(Expr::Literal(LiteralExpr { value: Some(right) })),
@@ -170,7 +783,17 @@ mod tests {
     }
 
     fn invoke_binary(left: Object, operator: Token, right: Object) -> Result<Object, LoxError> {
-        let terp = Interpreter {};
+        invoke_binary_with_policy(NumericPolicy::Ieee, left, operator, right)
+    }
+
+    fn invoke_binary_with_policy(
+        policy: NumericPolicy,
+        left: Object,
+        operator: Token,
+        right: Object,
+    ) -> Result<Object, LoxError> {
+        let mut terp = Interpreter::new();
+        terp.set_numeric_policy(policy);
         let binary_expr = BinaryExpr {
             left: Box::new(Expr::Literal(LiteralExpr { value: Some(left) })),
             operator,
@@ -246,6 +869,168 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn binary_int_overflow_promotes() {
+        let op1 = Token::new(TokenType::Plus, "+".to_string(), None, 10);
+        let result = invoke_binary(Object::Int(i64::MAX), op1, Object::Int(1));
+        assert_eq!(
+            result.ok(),
+            Some(Object::BigInt(BigInt::from(i64::MAX) + 1))
+        );
+
+        let op2 = Token::new(TokenType::Minus, "-".to_string(), None, 10);
+        let result2 = invoke_binary(
+            Object::BigInt(BigInt::from(i64::MAX) + 1),
+            op2,
+            Object::Int(1),
+        );
+        assert!(matches!(result2, Ok(Object::Int(i64::MAX))));
+    }
+
+    #[test]
+    fn binary_int_division() {
+        let slash = Token::new(TokenType::Slash, "/".to_string(), None, 10);
+        let result = invoke_binary(Object::Int(7), slash, Object::Int(2));
+        assert!(matches!(result, Ok(Object::Num(n)) if n == 3.5));
+
+        let tilde_slash = Token::new(TokenType::TildeSlash, "~/".to_string(), None, 10);
+        let result2 = invoke_binary(Object::Int(-7), tilde_slash.dup(), Object::Int(2));
+        assert!(matches!(result2, Ok(Object::Int(-4))));
+
+        let err_result = invoke_binary(Object::Int(7), tilde_slash, Object::Int(0));
+        assert!(err_result.is_err());
+    }
+
+    #[test]
+    fn binary_int_modulo() {
+        let op1 = Token::new(TokenType::Percent, "%".to_string(), None, 10);
+        let result = invoke_binary(Object::Int(-7), op1.dup(), Object::Int(3));
+        assert!(matches!(result, Ok(Object::Int(2))));
+
+        let result2 = invoke_binary(Object::Num(7.5), op1.dup(), Object::Int(2));
+        assert!(matches!(result2, Ok(Object::Num(n)) if n == 1.5));
+
+        let err_result = invoke_binary(Object::Int(7), op1, Object::Int(0));
+        assert!(err_result.is_err());
+    }
+
+    #[test]
+    fn binary_mixed_int_float() {
+        let op1 = Token::new(TokenType::Star, "*".to_string(), None, 10);
+        let result = invoke_binary(Object::Int(3), op1, Object::Num(1.5));
+        assert!(matches!(result, Ok(Object::Num(n)) if n == 4.5));
+
+        let op2 = Token::new(TokenType::EqualEqual, "==".to_string(), None, 10);
+        let result2 = invoke_binary(Object::Int(3), op2, Object::Num(3.0));
+        assert_eq!(result2.ok(), Some(Object::Bool(true)));
+    }
+
+    #[test]
+    fn numeric_policy_ieee() {
+        let op = Token::new(TokenType::Slash, "/".to_string(), None, 10);
+        let result = invoke_binary(Object::Num(1.0), op.dup(), Object::Num(0.0));
+        assert!(matches!(result, Ok(Object::Num(n)) if n == f64::INFINITY));
+
+        let result2 = invoke_binary(Object::Num(0.0), op, Object::Int(0));
+        assert!(matches!(result2, Ok(Object::Num(n)) if n.is_nan()));
+    }
+
+    #[test]
+    fn numeric_policy_error_on_zero_division() {
+        let policy = NumericPolicy::ErrorOnZeroDivision;
+        let op = Token::new(TokenType::Slash, "/".to_string(), None, 10);
+        let err_result =
+            invoke_binary_with_policy(policy, Object::Num(1.0), op.dup(), Object::Num(0.0));
+        assert!(err_result.is_err());
+
+        let err_result2 =
+            invoke_binary_with_policy(policy, Object::Int(1), op.dup(), Object::Int(0));
+        assert!(err_result2.is_err());
+
+        let result = invoke_binary_with_policy(policy, Object::Num(1.0), op, Object::Num(4.0));
+        assert!(matches!(result, Ok(Object::Num(n)) if n == 0.25));
+    }
+
+    #[test]
+    fn numeric_policy_error_on_nan() {
+        let policy = NumericPolicy::ErrorOnNan;
+        let op = Token::new(TokenType::Slash, "/".to_string(), None, 10);
+        let err_result =
+            invoke_binary_with_policy(policy, Object::Num(0.0), op.dup(), Object::Num(0.0));
+        assert!(err_result.is_err());
+
+        let result = invoke_binary_with_policy(policy, Object::Num(1.0), op, Object::Num(0.0));
+        assert!(matches!(result, Ok(Object::Num(n)) if n == f64::INFINITY));
+    }
+
+    fn strict() -> Interpreter {
+        let mut terp = Interpreter::new();
+        terp.set_dialect(Dialect::Strict);
+        terp
+    }
+
+    #[test]
+    fn strict_only_orders_numbers() {
+        let terp = strict();
+        let err = terp
+            .binary(
+                Object::Str("a".to_string()),
+                TokenType::Less,
+                Object::Str("b".to_string()),
+            )
+            .unwrap_err();
+        assert_eq!(err.message(), "Operands must be numbers.");
+
+        let result = terp.binary(Object::Int(1), TokenType::Less, Object::Num(1.5));
+        assert_eq!(result.ok(), Some(Object::Bool(true)));
+
+        let extended = Interpreter::new();
+        let result2 = extended.binary(
+            Object::Str("a".to_string()),
+            TokenType::Less,
+            Object::Str("b".to_string()),
+        );
+        assert_eq!(result2.ok(), Some(Object::Bool(true)));
+    }
+
+    #[test]
+    fn strict_equality_across_types() {
+        let terp = strict();
+        let result = terp.binary(Object::Nil, TokenType::EqualEqual, Object::Bool(false));
+        assert_eq!(result.ok(), Some(Object::Bool(false)));
+
+        let result2 = terp.binary(
+            Object::Int(1),
+            TokenType::EqualEqual,
+            Object::Str("1".to_string()),
+        );
+        assert_eq!(result2.ok(), Some(Object::Bool(false)));
+    }
+
+    #[test]
+    fn strict_stringify() {
+        let terp = strict();
+        assert_eq!(terp.stringify(&Object::Str("abc".to_string())), "abc");
+        assert_eq!(terp.stringify(&Object::Nil), "nil");
+        assert_eq!(terp.stringify(&Object::Bool(true)), "true");
+        assert_eq!(terp.stringify(&Object::Int(123)), "123");
+        assert_eq!(terp.stringify(&Object::Num(123.456)), "123.456");
+        assert_eq!(terp.stringify(&Object::Num(-0.0)), "-0");
+        assert_eq!(terp.stringify(&Object::Num(2.0)), "2");
+        assert_eq!(terp.stringify(&Object::Num(f64::NEG_INFINITY)), "-Infinity");
+        let list = List::new(vec![Object::Nil, Object::Str("s".to_string())]).into();
+        assert_eq!(terp.stringify(&list), "[nil, \"s\"]");
+        let map = Map::default();
+        map.set(&Object::Nil, list).unwrap();
+        assert_eq!(terp.stringify(&map.into()), "{nil: [nil, \"s\"]}");
+
+        let extended = Interpreter::new();
+        assert_eq!(
+            extended.stringify(&Object::Str("abc".to_string())),
+            "\"abc\""
+        );
+    }
+
     fn run_comparison_test(tok: &Token, cmps: Vec<bool>) {
         let nums = vec![-10.0, 0.0, 10.0];
         let comp_num = 0.0;
@@ -0,0 +1,40 @@
+pub mod ast_printer;
+pub mod buffer;
+mod builtins;
+pub mod convert;
+pub mod environment;
+pub mod error;
+mod exception;
+pub mod expr {
+    include!(concat!(env!("OUT_DIR"), "/expr.rs"));
+}
+pub mod formatter;
+pub mod interpreter;
+pub mod list;
+mod lox;
+pub mod map;
+mod math;
+pub mod module;
+pub mod native;
+pub mod object;
+pub mod optimizer;
+pub mod parser;
+pub mod scanner;
+pub mod stmt {
+    include!(concat!(env!("OUT_DIR"), "/stmt.rs"));
+}
+pub mod strings;
+mod system;
+pub mod token;
+pub mod token_type;
+pub mod userdata;
+
+pub use buffer::SharedBuffer;
+pub use convert::{FromLox, IntoLox};
+pub use error::{ErrorKind, LoxError};
+pub use list::List;
+pub use lox::Lox;
+pub use map::{Map, MapKey};
+pub use native::NativeFunction;
+pub use object::Object as Value;
+pub use userdata::{UserClass, UserData};
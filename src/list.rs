@@ -0,0 +1,210 @@
+use crate::convert::FromLox;
+use crate::error::LoxError;
+use crate::native::NativeFunction;
+use crate::object::Object;
+use std::cell::{Ref, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
+// A list of values. Copies of the value share the same elements, so a change
+// made through one is seen through all of them, and two lists are equal only
+// when they are the same list.
+#[derive(Clone, Default)]
+pub struct List {
+    elements: Rc<RefCell<Vec<Object>>>,
+}
+
+impl List {
+    pub fn new(elements: Vec<Object>) -> List {
+        List {
+            elements: Rc::new(RefCell::new(elements)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.borrow().is_empty()
+    }
+
+    pub fn elements(&self) -> Ref<'_, Vec<Object>> {
+        self.elements.borrow()
+    }
+
+    pub fn same_instance(&self, other: &List) -> bool {
+        Rc::ptr_eq(&self.elements, &other.elements)
+    }
+
+    // The list with each element written by `element`, e.g. the way a
+    // dialect shows it
+    pub fn show(&self, element: impl Fn(&Object) -> String) -> String {
+        // A list is only borrowed while it is being shown, so a list that
+        // can't be borrowed mutably is one that contains itself
+        if self.elements.try_borrow_mut().is_err() {
+            return "[...]".to_string();
+        }
+        let elements = self.elements.borrow();
+        let elements: Vec<String> = elements.iter().map(element).collect();
+        format!("[{}]", elements.join(", "))
+    }
+
+    // `xs[index]`
+    pub fn get(&self, index: &Object) -> Result<Object, LoxError> {
+        let elements = self.elements.borrow();
+        let index = list_index(index, elements.len())?;
+        Ok(elements[index].clone())
+    }
+
+    // `xs[index] = value`
+    pub fn set(&self, index: &Object, value: Object) -> Result<(), LoxError> {
+        let mut elements = self.elements.borrow_mut();
+        let index = list_index(index, elements.len())?;
+        elements[index] = value;
+        Ok(())
+    }
+
+    // Methods come back bound to this list, ready to be called
+    pub fn method(&self, name: &str) -> Result<Object, LoxError> {
+        let list = self.clone();
+        let function = match name {
+            "len" => NativeFunction::new(name, 0, move |_| Ok(Object::Int(list.len() as i64))),
+            "push" => NativeFunction::new(name, 1, move |args| {
+                list.elements.borrow_mut().push(args[0].clone());
+                Ok(Object::Nil)
+            }),
+            "pop" => NativeFunction::new(name, 0, move |_| {
+                list.elements
+                    .borrow_mut()
+                    .pop()
+                    .ok_or_else(|| LoxError::host_error("Can't pop from an empty list."))
+            }),
+            "insert" => NativeFunction::new(name, 2, move |args| {
+                let mut elements = list.elements.borrow_mut();
+                // Inserting at the length appends
+                let index = list_index(&args[0], elements.len() + 1)?;
+                elements.insert(index, args[1].clone());
+                Ok(Object::Nil)
+            }),
+            "remove" => NativeFunction::new(name, 1, move |args| {
+                let mut elements = list.elements.borrow_mut();
+                let index = list_index(&args[0], elements.len())?;
+                Ok(elements.remove(index))
+            }),
+            // From `start` up to but not including `end`, as a new list
+            "slice" => NativeFunction::new(name, 2, move |args| {
+                let elements = list.elements.borrow();
+                let end = list_index(&args[1], elements.len() + 1)?;
+                let start = list_index(&args[0], end + 1)?;
+                Ok(List::new(elements[start..end].to_vec()).into())
+            }),
+            _ => {
+                return Err(LoxError::host_error(&format!(
+                    "Undefined property '{name}'."
+                )))
+            }
+        };
+        Ok(function.into())
+    }
+}
+
+// Checks that `index` is a whole number below `len`, for indexing anything
+// of the given kind. Floats are accepted as long as they are whole, since the
+// strict dialect has no integers.
+pub(crate) fn check_index(kind: &str, index: &Object, len: usize) -> Result<usize, LoxError> {
+    let position = match index {
+        Object::Int(_) | Object::BigInt(_) => i64::from_lox(index).ok(),
+        Object::Num(n) if n.fract() == 0.0 => Some(*n as i64),
+        _ => {
+            return Err(LoxError::host_error(&format!(
+                "{kind} index must be an integer but got {}.",
+                index.type_name()
+            )))
+        }
+    };
+    match position.and_then(|i| usize::try_from(i).ok()) {
+        Some(i) if i < len => Ok(i),
+        _ => Err(LoxError::host_error(&format!(
+            "{kind} index {index} is out of bounds."
+        ))),
+    }
+}
+
+fn list_index(index: &Object, len: usize) -> Result<usize, LoxError> {
+    check_index("List", index, len)
+}
+
+impl fmt::Display for List {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.show(|element| element.to_string()))
+    }
+}
+
+impl fmt::Debug for List {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "List({self})")
+    }
+}
+
+impl From<List> for Object {
+    fn from(list: List) -> Object {
+        Object::List(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(list: &List, name: &str, args: &[Object]) -> Result<Object, LoxError> {
+        match list.method(name)? {
+            Object::NativeFunction(function) => function.call(args),
+            _ => panic!("{name} should be a function"),
+        }
+    }
+
+    #[test]
+    fn methods() {
+        let list = List::new(vec![Object::Int(1), Object::Int(2)]);
+        call(&list, "push", &[Object::Int(3)]).unwrap();
+        call(&list, "insert", &[Object::Int(0), Object::Int(0)]).unwrap();
+        assert_eq!(list.to_string(), "[0, 1, 2, 3]");
+        assert_eq!(call(&list, "pop", &[]).unwrap(), Object::Int(3));
+        assert_eq!(
+            call(&list, "remove", &[Object::Int(1)]).unwrap(),
+            Object::Int(1)
+        );
+        assert_eq!(call(&list, "len", &[]).unwrap(), Object::Int(2));
+
+        let slice = call(&list, "slice", &[Object::Int(1), Object::Int(2)]).unwrap();
+        assert_eq!(slice.to_string(), "[2]");
+        assert!(call(&list, "slice", &[Object::Int(2), Object::Int(1)]).is_err());
+        assert!(call(&List::default(), "pop", &[]).is_err());
+    }
+
+    #[test]
+    fn indexes_are_checked() {
+        let list = List::new(vec![Object::Nil]);
+        assert_eq!(list.get(&Object::Int(0)).unwrap(), Object::Nil);
+        let err = list.get(&Object::Int(1)).unwrap_err();
+        assert_eq!(err.message(), "List index 1 is out of bounds.");
+        assert!(list.set(&Object::Int(-1), Object::Nil).is_err());
+        assert_eq!(list.get(&Object::Num(0.0)).unwrap(), Object::Nil);
+        let err = list.get(&Object::Num(0.5)).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "List index must be an integer but got float."
+        );
+    }
+
+    #[test]
+    fn copies_share_elements() {
+        let list = List::default();
+        let copy = Object::from(list.clone());
+        call(&list, "push", std::slice::from_ref(&copy)).unwrap();
+        assert_eq!(copy.to_string(), "[[...]]");
+        assert_eq!(copy, Object::from(list));
+        assert_ne!(copy, Object::from(List::default()));
+    }
+}
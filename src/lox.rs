@@ -0,0 +1,369 @@
+use crate::error::LoxError;
+use crate::formatter::Formatter;
+use crate::interpreter::Interpreter;
+use crate::native::NativeFunction;
+use crate::object::Object;
+use crate::optimizer::ConstantFolder;
+use crate::parser::Parser;
+use crate::scanner::Scanner;
+use crate::stmt::*;
+use crate::token::Token;
+use crate::token_type::TokenType;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+// A Lox session for embedding: each stage of the pipeline can be run on its
+// own, or `eval` runs source all the way through. Errors are reported to the
+// interpreter's error output as they are found, the same way the command line
+// shows them, and the last one is returned to the caller.
+#[derive(Default)]
+pub struct Lox {
+    interpreter: Interpreter,
+}
+
+impl Lox {
+    pub fn new() -> Lox {
+        Lox::default()
+    }
+
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    pub fn interpreter_mut(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    // Binds a host function to a global name; scripts call it like any other
+    // function, with exactly `arity` arguments.
+    pub fn register_fn<F>(&self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Object]) -> Result<Object, LoxError> + 'static,
+    {
+        self.interpreter
+            .define(name, NativeFunction::new(name, arity, function).into());
+    }
+
+    // Binds any value, e.g. a host object, to a global name
+    pub fn define(&self, name: &str, value: Object) {
+        self.interpreter.define(name, value);
+    }
+
+    // Starts over with no variables; settings and streams are kept
+    pub fn reset(&self) {
+        self.interpreter.reset();
+    }
+
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.interpreter.set_output(output);
+    }
+
+    pub fn set_error_output(&mut self, error_output: Box<dyn Write>) {
+        self.interpreter.set_error_output(error_output);
+    }
+
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.interpreter.set_input(input);
+    }
+
+    pub fn scan(&self, source: &str) -> Result<Vec<Token>, LoxError> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.set_dialect(self.interpreter.dialect());
+        let tokens = scanner.scan_tokens().cloned();
+        self.report_all(scanner.errors());
+        tokens
+    }
+
+    pub fn parse(&self, source: &str) -> Result<Vec<Stmt>, LoxError> {
+        self.parse_source(source, false)
+    }
+
+    // Like `parse`, but the final expression statement doesn't need its `;`
+    pub fn parse_line(&self, source: &str) -> Result<Vec<Stmt>, LoxError> {
+        self.parse_source(source, true)
+    }
+
+    // Parses and constant folds, ready for the interpreter
+    pub fn compile(&self, source: &str) -> Result<Vec<Stmt>, LoxError> {
+        self.optimize(self.parse(source)?)
+    }
+
+    // The tree doesn't keep comments, so a script that has any is refused
+    // rather than formatted without them
+    pub fn format(&self, source: &str) -> Result<String, LoxError> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.set_dialect(self.interpreter.dialect());
+        let _ = scanner.scan_tokens();
+        if let Some(&line) = scanner.comments().first() {
+            let err = LoxError::error(line, "Can't format a script that has comments.");
+            self.interpreter.report(&err);
+            return Err(err);
+        }
+        let statements = self.parse(source)?;
+        Formatter
+            .format(&statements)
+            .inspect_err(|err| self.interpreter.report(err))
+    }
+
+    // Runs the source and returns the value of its final statement when that
+    // is an expression, and nil otherwise.
+    pub fn eval(&self, source: &str) -> Result<Object, LoxError> {
+        let statements = self.compile(source)?;
+        let value = self.execute(&statements)?;
+        Ok(value.unwrap_or(Object::Nil))
+    }
+
+    // Runs a line typed at a prompt, where the final expression statement
+    // doesn't need its `;`. Its value is returned so the prompt can show it.
+    pub fn eval_line(&self, source: &str) -> Result<Option<Object>, LoxError> {
+        let statements = self.optimize(self.parse_line(source)?)?;
+        self.execute(&statements)
+    }
+
+    // False while the source has unclosed brackets or an unterminated string,
+    // i.e. when a prompt should read another line before running it
+    pub fn is_complete(source: &str) -> bool {
+        let mut scanner = Scanner::new(source.to_string());
+        let Ok(tokens) = scanner.scan_tokens() else {
+            return !scanner
+                .errors()
+                .iter()
+                .any(|err| err.message() == "Unterminated string.");
+        };
+        let depth = tokens.iter().fold(0, |depth, token| match token.ttype {
+            TokenType::LeftParen | TokenType::LeftBrace | TokenType::LeftBracket => depth + 1,
+            TokenType::RightParen | TokenType::RightBrace | TokenType::RightBracket => depth - 1,
+            _ => depth,
+        });
+        depth <= 0
+    }
+
+    pub fn run_file<P: AsRef<Path>>(&self, path: P) -> Result<Object, LoxError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|err| LoxError::io_error(&path.display().to_string(), &err))
+            .inspect_err(|err| self.interpreter.report(err))?;
+        self.interpreter.in_file(path, || self.eval(&source))
+    }
+
+    fn parse_source(&self, source: &str, bare_expression: bool) -> Result<Vec<Stmt>, LoxError> {
+        let tokens = self.scan(source)?;
+        let mut parser = Parser::new(&tokens);
+        parser.set_bare_expression(bare_expression);
+        let statements = parser.parse();
+        self.report_all(parser.errors());
+        statements
+    }
+
+    fn optimize(&self, statements: Vec<Stmt>) -> Result<Vec<Stmt>, LoxError> {
+        ConstantFolder::new(&self.interpreter)
+            .optimize(statements)
+            .inspect_err(|err| self.interpreter.report(err))
+    }
+
+    fn report_all(&self, errors: &[LoxError]) {
+        for err in errors {
+            self.interpreter.report(err);
+        }
+    }
+
+    // The value of the final statement, if it's an expression
+    fn execute(&self, statements: &[Stmt]) -> Result<Option<Object>, LoxError> {
+        let result = match statements.split_last() {
+            Some((Stmt::Expression(last), rest)) => self
+                .interpreter
+                .interpret(rest)
+                .and_then(|()| self.interpreter.evaluate(&last.expression))
+                .map(Some),
+            _ => self.interpreter.interpret(statements).map(|()| None),
+        };
+        result.inspect_err(|err| self.interpreter.report(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::SharedBuffer;
+    use crate::convert::{FromLox, IntoLox};
+    use crate::error::ErrorKind;
+    use crate::userdata::{UserClass, UserData};
+
+    #[test]
+    fn eval_returns_the_last_expression() {
+        let lox = Lox::new();
+        assert_eq!(lox.eval("1 + 2;").unwrap(), Object::Int(3));
+        assert_eq!(
+            lox.eval("print 1; \"a\" + \"b\";").unwrap(),
+            Object::Str("ab".to_string())
+        );
+        assert_eq!(lox.eval("print 1;").unwrap(), Object::Nil);
+        assert_eq!(lox.eval("").unwrap(), Object::Nil);
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let lox = Lox::new();
+        assert_eq!(lox.eval("1 +;").unwrap_err().kind(), ErrorKind::Compile);
+        assert_eq!(lox.eval("-\"a\";").unwrap_err().kind(), ErrorKind::Runtime);
+
+        let err = lox.run_file("no/such/file.lox").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+    }
+
+    #[test]
+    fn calls_native_functions() {
+        let lox = Lox::new();
+        lox.register_fn("hypot", 2, |args| {
+            let (x, y) = (f64::from_lox(&args[0])?, f64::from_lox(&args[1])?);
+            Ok(x.hypot(y).into_lox())
+        });
+        assert_eq!(lox.eval("hypot(3, 4) + 1;").unwrap(), Object::Num(6.0));
+        assert_eq!(lox.eval("hypot == hypot;").unwrap(), Object::Bool(true));
+
+        let err = lox.eval("hypot(1);").unwrap_err();
+        assert_eq!(err.message(), "Expected 2 arguments but got 1.");
+
+        // Errors raised by the host are placed at the call
+        let err = lox.eval("\n\nhypot(1, \"2\");").unwrap_err();
+        assert_eq!(err.message(), "Expected a number but got string.");
+        assert_eq!(err.line(), 3);
+    }
+
+    #[test]
+    fn exposes_host_objects() {
+        struct Point {
+            x: f64,
+        }
+        let class = UserClass::builder::<Point>("Point")
+            .property(
+                "x",
+                |p| p.x.into_lox(),
+                |p, x| {
+                    p.x = f64::from_lox(&x)?;
+                    Ok(())
+                },
+            )
+            .method("scale", 1, |p, args| {
+                p.x *= f64::from_lox(&args[0])?;
+                Ok(Object::Nil)
+            })
+            .build();
+        let lox = Lox::new();
+        lox.define("p", UserData::new(&class, Point { x: 1.5 }).into());
+
+        assert_eq!(
+            lox.eval("p.x = 2; p.scale(3); p.x;").unwrap(),
+            Object::Num(6.0)
+        );
+        assert_eq!(lox.eval("p == p;").unwrap(), Object::Bool(true));
+
+        let err = lox.eval("p.y;").unwrap_err();
+        assert_eq!(err.message(), "Undefined property 'y'.");
+        let err = lox.eval("1.x = 2;").unwrap_err();
+        assert_eq!(err.message(), "Only instances have fields.");
+    }
+
+    #[test]
+    fn has_a_string_library() {
+        let lox = Lox::new();
+        let eval = |source: &str| lox.eval(source).unwrap().to_string();
+        assert_eq!(
+            eval("\" a-b \".trim().upper().split(\"-\");"),
+            "[\"A\", \"B\"]"
+        );
+        assert_eq!(
+            eval("\", \".join([\"x\", str(1.5), str(nil)]);"),
+            "\"x, 1.5, Nil\""
+        );
+        assert_eq!(eval("num(\"12\") + num(\" -0.5\");"), "11.5");
+        assert_eq!(eval("num(\"twelve\");"), "Nil");
+        assert_eq!(
+            eval("[type(1), type(\"\"), type([]), type(str)];"),
+            "[\"int\", \"string\", \"list\", \"function\"]"
+        );
+        assert!(lox.eval("num(true);").is_err());
+
+        // Resetting keeps the built-in functions
+        lox.reset();
+        assert_eq!(eval("str(1 == 1);"), "\"true\"");
+    }
+
+    #[test]
+    fn runtime_errors_show_the_calls() {
+        let errors = SharedBuffer::new();
+        let mut lox = Lox::new();
+        lox.set_error_output(Box::new(errors.clone()));
+        lox.register_fn("fail", 0, |_| Err(LoxError::host_error("Failed.")));
+
+        let err = lox.eval("print 1;\nfail();").unwrap_err();
+        assert_eq!(err.trace().collect::<Vec<_>>(), ["fail"]);
+        assert_eq!(
+            errors.contents(),
+            "Failed.\n[line 2] in fail()\n[line 2] in script\n"
+        );
+
+        // Errors outside any call keep the reference layout
+        errors.clear();
+        assert!(lox.eval("fail(1);").is_err());
+        assert_eq!(
+            errors.contents(),
+            "Expected 0 arguments but got 1.\n[line 1]\n"
+        );
+    }
+
+    #[test]
+    fn output_can_be_captured() {
+        let output = SharedBuffer::new();
+        let errors = SharedBuffer::new();
+        let mut lox = Lox::new();
+        lox.set_output(Box::new(output.clone()));
+        lox.set_error_output(Box::new(errors.clone()));
+        lox.set_input(Box::new("first\nsecond".as_bytes()));
+
+        lox.eval("print 1 + 2; print \"a\";").unwrap();
+        assert_eq!(output.contents(), "3\n\"a\"\n");
+
+        assert!(lox.eval("print;\n-nil;\n@").is_err());
+        assert_eq!(errors.contents(), "[line 3] Error: Unexpected character.\n");
+        errors.clear();
+        assert!(lox.eval("print;\n1 +;").is_err());
+        assert_eq!(
+            errors.contents(),
+            "[line 1] Error at ';': Expect expression.\n\
+             [line 2] Error at ';': Expect expression.\n"
+        );
+
+        let interpreter = lox.interpreter();
+        assert_eq!(interpreter.read_line().unwrap().as_deref(), Some("first"));
+        assert_eq!(interpreter.read_line().unwrap().as_deref(), Some("second"));
+        assert_eq!(interpreter.read_line().unwrap(), None);
+    }
+
+    #[test]
+    fn sessions_keep_their_globals() {
+        let lox = Lox::new();
+        assert_eq!(lox.eval_line("var a = 1;").unwrap(), None);
+        assert_eq!(lox.eval_line("a = a + 1").unwrap(), Some(Object::Int(2)));
+        assert_eq!(lox.eval_line("{ var a = 10; }").unwrap(), None);
+        assert_eq!(lox.eval_line("a").unwrap(), Some(Object::Int(2)));
+        assert!(lox.eval("a").is_err());
+    }
+
+    #[test]
+    fn detects_incomplete_input() {
+        assert!(Lox::is_complete("print 1;"));
+        assert!(Lox::is_complete("print );"));
+        assert!(!Lox::is_complete("{ var a = (1 +"));
+        assert!(!Lox::is_complete("print \"abc"));
+        assert!(Lox::is_complete("{ print (1); }"));
+    }
+
+    #[test]
+    fn stages_can_run_separately() {
+        let lox = Lox::new();
+        assert_eq!(lox.scan("1 + 2;").unwrap().len(), 5);
+        assert_eq!(lox.parse("1; 2;").unwrap().len(), 2);
+        assert!(lox.scan("@").is_err());
+    }
+}
@@ -1,95 +1,217 @@
-mod ast_printer;
-mod error;
-mod expr;
-mod interpreter;
-mod object;
-mod parser;
-mod scanner;
-mod token;
-mod token_type;
-
-use ast_printer::*;
-use error::*;
-use interpreter::*;
-use parser::*;
-use scanner::*;
+mod repl;
+
+use lox_ast::ast_printer::AstPrinter;
+use lox_ast::interpreter::*;
+use lox_ast::{ErrorKind, Lox, LoxError};
 use std::env::args;
-use std::io::{self, stdout, BufRead, Write};
+use std::io::{self, Read};
+use std::process::exit;
 
-pub fn main() {
-    let args: Vec<String> = args().collect();
-    let lox = Lox::new();
-    // println!("args: {:?}", args);
-
-    match args.len() {
-        1 => lox.run_prompt().expect("Could not flush stdout"),
-        2 => lox.run_file(&args[1]).expect("Could not run file"),
-        _ => {
-            println!("Usage: lox-ast [script]");
-            std::process::exit(64);
-        }
-    }
+// Exit statuses from sysexits.h
+const EX_OK: i32 = 0;
+const EX_USAGE: i32 = 64;
+const EX_DATAERR: i32 = 65;
+const EX_NOINPUT: i32 = 66;
+const EX_SOFTWARE: i32 = 70;
+const EX_IOERR: i32 = 74;
+
+const USAGE: &str = "\
+Usage: lox-ast [options] [script | -] [arguments]
+       lox-ast [options] <command> [script | -] [arguments]
+       lox-ast [options] [command] -e <code> [arguments]
+
+Commands:
+  run     run the script (the default when there is one)
+  repl    start an interactive prompt (the default when there isn't)
+  check   report errors in the script without running it
+  fmt     print the script in the standard layout
+  tokens  print the tokens scanned from the script
+  ast     print the syntax tree of each statement
+
+A script named `-` is read from standard input. Anything after the script
+is passed to it, and `args()` returns it.
+
+Options:
+  -e <code>           use <code> as the script
+  --strict            follow the reference Lox semantics and output
+  --numeric=<policy>  ieee (the default), error-on-zero or error-on-nan
+  --module-path=<dirs>
+                      where to look for imports not found beside the
+                      importing file, separated like PATH
+  --allow-read        let the script read input and files, and import any file
+  --allow-write       let the script write files
+  --allow-env         let the script read environment variables
+  --allow-all         all of the above
+  -h, --help          show this message";
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Command {
+    Run,
+    Repl,
+    Check,
+    Fmt,
+    Tokens,
+    Ast,
 }
 
-struct Lox {
-    _printer: AstPrinter,
-    interpreter: Interpreter,
+enum Input {
+    File(String),
+    Stdin,
+    Inline(String),
 }
 
-impl Lox {
-    pub fn new() -> Lox {
-        Lox {
-            _printer: AstPrinter {},
-            interpreter: Interpreter {},
+impl Command {
+    fn named(name: &str) -> Option<Command> {
+        match name {
+            "run" => Some(Command::Run),
+            "repl" => Some(Command::Repl),
+            "check" => Some(Command::Check),
+            "fmt" => Some(Command::Fmt),
+            "tokens" => Some(Command::Tokens),
+            "ast" => Some(Command::Ast),
+            _ => None,
         }
     }
+}
 
-    pub fn run_file(&self, path: &str) -> io::Result<()> {
-        let buf = std::fs::read_to_string(path)?;
-        if self.run(buf).is_err() {
-            // Ignore: error was already reported
-            std::process::exit(65);
+impl Input {
+    fn named(name: String) -> Input {
+        if name == "-" {
+            Input::Stdin
+        } else {
+            Input::File(name)
         }
+    }
 
-        Ok(())
+    fn read(self) -> Result<String, LoxError> {
+        match self {
+            Input::File(path) => {
+                std::fs::read_to_string(&path).map_err(|err| LoxError::io_error(&path, &err))
+            }
+            Input::Stdin => {
+                let mut source = String::new();
+                io::stdin()
+                    .read_to_string(&mut source)
+                    .map_err(|err| LoxError::io_error("standard input", &err))?;
+                Ok(source)
+            }
+            Input::Inline(source) => Ok(source),
+        }
     }
+}
 
-    pub fn run_prompt(&self) -> io::Result<()> {
-        let stdin = io::stdin();
-        print!("> ");
-        stdout().flush()?;
-        for line in stdin.lock().lines() {
-            if let Ok(line) = line {
-                if line.is_empty() {
-                    break;
-                }
-                let _ = self.run(line);
-            } else {
-                break;
+pub fn main() {
+    let mut lox = Lox::new();
+    let mut command = None;
+    let mut input = None;
+    let mut capabilities = Capabilities::default();
+
+    let mut args = args().skip(1);
+    while let Some(arg) = args.next() {
+        // The rest belongs to the script
+        if input.is_some() {
+            lox.interpreter_mut()
+                .set_args(std::iter::once(arg).chain(args).collect());
+            break;
+        }
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                exit(EX_OK);
+            }
+            "--strict" => lox.interpreter_mut().set_dialect(Dialect::Strict),
+            "--allow-read" => capabilities.read = true,
+            "--allow-write" => capabilities.write = true,
+            "--allow-env" => capabilities.env = true,
+            "--allow-all" => capabilities = Capabilities::all(),
+            "-e" => {
+                input = Some(Input::Inline(args.next().unwrap_or_else(|| usage())));
             }
-            print!("> ");
-            stdout().flush()?;
+            _ if arg.starts_with("--numeric=") => {
+                let policy = match &arg["--numeric=".len()..] {
+                    "ieee" => NumericPolicy::Ieee,
+                    "error-on-zero" => NumericPolicy::ErrorOnZeroDivision,
+                    "error-on-nan" => NumericPolicy::ErrorOnNan,
+                    _ => usage(),
+                };
+                lox.interpreter_mut().set_numeric_policy(policy);
+            }
+            _ if arg.starts_with("--module-path=") => {
+                let dirs = std::env::split_paths(&arg["--module-path=".len()..]).collect();
+                lox.interpreter_mut().set_module_path(dirs);
+            }
+            _ if arg.starts_with('-') && arg != "-" => usage(),
+            // A command can only come before the script
+            _ if command.is_none() && Command::named(&arg).is_some() => {
+                command = Command::named(&arg);
+            }
+            _ => input = Some(Input::named(arg)),
+        }
+    }
+    lox.interpreter_mut().set_capabilities(capabilities);
+
+    let status = match (command, input) {
+        (None | Some(Command::Repl), None) => repl(lox),
+        (Some(Command::Repl), Some(_)) | (Some(_), None) => usage(),
+        (command, Some(input)) => match execute(&lox, command.unwrap_or(Command::Run), input) {
+            Ok(()) => EX_OK,
+            Err(err) => exit_status(&err),
+        },
+    };
+    exit(status);
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    exit(EX_USAGE);
+}
+
+fn exit_status(err: &LoxError) -> i32 {
+    match err.kind() {
+        ErrorKind::Compile => EX_DATAERR,
+        ErrorKind::Runtime => EX_SOFTWARE,
+        ErrorKind::Io => EX_NOINPUT,
+        ErrorKind::Exit(status) => status,
+    }
+}
+
+fn repl(lox: Lox) -> i32 {
+    match repl::run(lox) {
+        Ok(status) => status,
+        Err(err) => {
+            eprintln!("Could not read input: {err}");
+            EX_IOERR
         }
-        Ok(())
     }
+}
 
-    pub fn run(&self, source: String) -> Result<(), LoxError> {
-        let mut scanner = Scanner::new(source);
-        let tokens = scanner.scan_tokens()?;
-        let mut parser = Parser::new(tokens);
-        let expr = parser.parse();
-
-        match expr {
-            None => {}
-            Some(expr) => {
-                // let printer1 = AstPrinter {};
-                // println!("AstPrint: {}", printer1.print(&expr)?);
-                match self.interpreter.interpret(&expr) {
-                    Ok(_) => println!("yay"),
-                    Err(_) => println!("boo"),
-                }
+// Errors have been reported by the time they're returned
+fn execute(lox: &Lox, command: Command, input: Input) -> Result<(), LoxError> {
+    // Imports are found relative to the script
+    if let (Command::Run, Input::File(path)) = (command, &input) {
+        return lox.run_file(path).map(|_| ());
+    }
+    let report = |err: &LoxError| lox.interpreter().report(err);
+    let source = input.read().inspect_err(report)?;
+    match command {
+        Command::Run => lox.eval(&source).map(|_| ()),
+        Command::Repl => unreachable!("the prompt doesn't take a script"),
+        Command::Check => lox.compile(&source).map(|_| ()),
+        Command::Fmt => {
+            print!("{}", lox.format(&source)?);
+            Ok(())
+        }
+        Command::Tokens => {
+            for token in lox.scan(&source)? {
+                println!("{token}");
+            }
+            Ok(())
+        }
+        Command::Ast => {
+            for stmt in lox.parse(&source)? {
+                println!("{}", AstPrinter.print_stmt(&stmt).inspect_err(report)?);
             }
+            Ok(())
         }
-        Ok(())
     }
 }
@@ -0,0 +1,282 @@
+use crate::error::LoxError;
+use crate::list::List;
+use crate::native::NativeFunction;
+use crate::object::Object;
+use indexmap::IndexMap;
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
+use std::cell::RefCell;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+// A value that can be used as a map key: a number other than NaN, a string,
+// a bool, nil, or an instance, which is compared by identity.
+//
+// Object can't be Eq itself, since NaN isn't equal to itself, and lists and
+// maps can change while they're stored. Keys are equal when the values are
+// `==`, so 1 and 1.0 are the same key, except that an integer and a float are
+// compared exactly rather than as floats.
+#[derive(Debug, Clone)]
+pub struct MapKey(Object);
+
+impl MapKey {
+    pub fn new(value: Object) -> Result<MapKey, LoxError> {
+        match &value {
+            Object::Num(n) if n.is_nan() => {
+                Err(LoxError::host_error("Can't use NaN as a map key."))
+            }
+            Object::Num(_)
+            | Object::Int(_)
+            | Object::BigInt(_)
+            | Object::Str(_)
+            | Object::Bool(_)
+            | Object::Nil
+            | Object::UserData(_) => Ok(MapKey(value)),
+            _ => Err(LoxError::host_error(&format!(
+                "Can't use a {} as a map key.",
+                value.type_name()
+            ))),
+        }
+    }
+
+    pub fn value(&self) -> &Object {
+        &self.0
+    }
+}
+
+impl PartialEq for MapKey {
+    fn eq(&self, other: &MapKey) -> bool {
+        match (&self.0, &other.0) {
+            // As floats, 2^53 + 1 would equal 2.0^53 which equals 2^53, but
+            // equality has to be transitive for keys
+            (Object::Num(n), i @ (Object::Int(_) | Object::BigInt(_)))
+            | (i @ (Object::Int(_) | Object::BigInt(_)), Object::Num(n)) => {
+                n.fract() == 0.0 && BigInt::from_f64(*n) == i.as_bigint()
+            }
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl Eq for MapKey {}
+
+impl Hash for MapKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self.0 {
+            // Numbers that are == convert to the same float, whatever their
+            // representation; zero's sign is dropped since -0.0 == 0.0
+            Object::Num(_) | Object::Int(_) | Object::BigInt(_) => {
+                0u8.hash(state);
+                let n = self.0.as_f64().unwrap_or(f64::NAN);
+                let n = if n == 0.0 { 0.0 } else { n };
+                n.to_bits().hash(state);
+            }
+            Object::Str(s) => {
+                1u8.hash(state);
+                s.hash(state);
+            }
+            Object::Bool(b) => {
+                2u8.hash(state);
+                b.hash(state);
+            }
+            Object::Nil => 3u8.hash(state),
+            Object::UserData(u) => {
+                4u8.hash(state);
+                u.address().hash(state);
+            }
+            _ => unreachable!("MapKey::new only accepts hashable values"),
+        }
+    }
+}
+
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// A map from keys to values that remembers the order keys were first added
+// in. Like lists, copies share the same entries and two maps are equal only
+// when they are the same map.
+#[derive(Clone, Default)]
+pub struct Map {
+    entries: Rc<RefCell<IndexMap<MapKey, Object>>>,
+}
+
+impl Map {
+    pub fn new(entries: IndexMap<MapKey, Object>) -> Map {
+        Map {
+            entries: Rc::new(RefCell::new(entries)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    pub fn same_instance(&self, other: &Map) -> bool {
+        Rc::ptr_eq(&self.entries, &other.entries)
+    }
+
+    // `m[key]`
+    pub fn get(&self, key: &Object) -> Result<Object, LoxError> {
+        let key = MapKey::new(key.clone())?;
+        self.entries
+            .borrow()
+            .get(&key)
+            .cloned()
+            .ok_or_else(|| Map::missing(&key))
+    }
+
+    // `m[key] = value`; a new key goes after the existing ones
+    pub fn set(&self, key: &Object, value: Object) -> Result<(), LoxError> {
+        let key = MapKey::new(key.clone())?;
+        self.entries.borrow_mut().insert(key, value);
+        Ok(())
+    }
+
+    pub fn has(&self, key: &Object) -> bool {
+        MapKey::new(key.clone()).is_ok_and(|key| self.entries.borrow().contains_key(&key))
+    }
+
+    pub fn keys(&self) -> Vec<Object> {
+        let entries = self.entries.borrow();
+        entries.keys().map(|key| key.value().clone()).collect()
+    }
+
+    pub fn values(&self) -> Vec<Object> {
+        self.entries.borrow().values().cloned().collect()
+    }
+
+    // Methods come back bound to this map, ready to be called
+    pub fn method(&self, name: &str) -> Result<Object, LoxError> {
+        let map = self.clone();
+        let function = match name {
+            "len" => NativeFunction::new(name, 0, move |_| Ok(Object::Int(map.len() as i64))),
+            "keys" => NativeFunction::new(name, 0, move |_| Ok(List::new(map.keys()).into())),
+            "values" => NativeFunction::new(name, 0, move |_| Ok(List::new(map.values()).into())),
+            "has" => NativeFunction::new(name, 1, move |args| Ok(Object::Bool(map.has(&args[0])))),
+            // Returns the value that was removed; the other keys keep their order
+            "remove" => NativeFunction::new(name, 1, move |args| {
+                let key = MapKey::new(args[0].clone())?;
+                let removed = map.entries.borrow_mut().shift_remove(&key);
+                removed.ok_or_else(|| Map::missing(&key))
+            }),
+            _ => {
+                return Err(LoxError::host_error(&format!(
+                    "Undefined property '{name}'."
+                )))
+            }
+        };
+        Ok(function.into())
+    }
+
+    fn missing(key: &MapKey) -> LoxError {
+        LoxError::host_error(&format!("Key {key} is not in the map."))
+    }
+
+    // The map with each key and value written by `value`, as with `List::show`
+    pub fn show(&self, value: impl Fn(&Object) -> String) -> String {
+        // As with lists, a map that's already borrowed contains itself
+        if self.entries.try_borrow_mut().is_err() {
+            return "{...}".to_string();
+        }
+        let entries = self.entries.borrow();
+        let entries: Vec<String> = entries
+            .iter()
+            .map(|(k, v)| format!("{}: {}", value(k.value()), value(v)))
+            .collect();
+        format!("{{{}}}", entries.join(", "))
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.show(|value| value.to_string()))
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Map({self})")
+    }
+}
+
+impl From<Map> for Object {
+    fn from(map: Map) -> Object {
+        Object::Map(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash(value: Object) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        MapKey::new(value).unwrap().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equal_numbers_are_the_same_key() {
+        assert_eq!(hash(Object::Int(1)), hash(Object::Num(1.0)));
+        assert_eq!(hash(Object::Num(0.0)), hash(Object::Num(-0.0)));
+        let big = BigInt::from(2).pow(70);
+        assert_eq!(hash(Object::BigInt(big)), hash(Object::Num(2f64.powi(70))));
+        assert_ne!(hash(Object::Int(1)), hash(Object::Str("1".to_string())));
+    }
+
+    #[test]
+    fn integers_and_floats_are_compared_exactly() {
+        let key = |value| MapKey::new(value).unwrap();
+        let exact = Object::Int(1 << 53);
+        let above = Object::Int((1 << 53) + 1);
+        let float = Object::Num(2f64.powi(53));
+        // `==` compares them as floats, so it finds all three equal
+        assert!(above == float && float == exact);
+        assert_eq!(key(float.clone()), key(exact.clone()));
+        assert_ne!(key(float.clone()), key(above.clone()));
+        assert_ne!(key(exact.clone()), key(above.clone()));
+        assert_ne!(key(Object::Num(0.5)), key(Object::Int(0)));
+
+        let map = Map::default();
+        for value in [exact, above, float] {
+            map.set(&value, Object::Nil).unwrap();
+        }
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn only_stable_values_are_keys() {
+        assert!(MapKey::new(Object::Num(f64::NAN)).is_err());
+        let err = MapKey::new(List::default().into()).unwrap_err();
+        assert_eq!(err.message(), "Can't use a list as a map key.");
+    }
+
+    #[test]
+    fn keeps_insertion_order() {
+        let map = Map::default();
+        for key in ["b", "a", "c"] {
+            map.set(&Object::Str(key.to_string()), Object::Nil).unwrap();
+        }
+        map.set(&Object::Str("b".to_string()), Object::Int(1))
+            .unwrap();
+        assert_eq!(map.to_string(), "{\"b\": 1, \"a\": Nil, \"c\": Nil}");
+
+        let remove = match map.method("remove").unwrap() {
+            Object::NativeFunction(function) => function,
+            _ => panic!("remove should be a function"),
+        };
+        remove.call(&[Object::Str("a".to_string())]).unwrap();
+        assert_eq!(map.keys().len(), 2);
+        assert_eq!(map.keys()[1], Object::Str("c".to_string()));
+        assert!(map.get(&Object::Str("a".to_string())).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use crate::convert::{FromLox, IntoLox};
+use crate::error::LoxError;
+use crate::object::Object;
+use crate::userdata::{UserClass, UserData};
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive};
+use std::cmp::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The generator behind `Math.random()`. It's seeded from the clock, and
+// `Math.seed(n)` restarts it so that a script can get the same numbers on
+// every run.
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn from_clock() -> Random {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Random { state: now }
+    }
+
+    // SplitMix64: small, fast, and good enough for scripts
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1), from the top 53 bits
+    fn next_f64(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn number(value: &Object) -> Result<f64, LoxError> {
+    f64::from_lox(value)
+}
+
+// Rounding gives an integer whenever the result fits in one
+fn whole(n: f64) -> Object {
+    match n.to_i64() {
+        Some(i) => Object::Int(i),
+        None => Object::Num(n),
+    }
+}
+
+// Rounding an integer leaves it alone; anything else must be a number
+fn rounded(value: &Object, round: fn(f64) -> f64) -> Result<Object, LoxError> {
+    match value {
+        Object::Int(_) | Object::BigInt(_) => Ok(value.clone()),
+        _ => Ok(whole(round(number(value)?))),
+    }
+}
+
+// The smaller or larger of two numbers, as given
+fn pick(args: &[Object], wanted: Ordering) -> Result<Object, LoxError> {
+    let (a, b) = (&args[0], &args[1]);
+    number(a)?;
+    number(b)?;
+    match a.compare(b).ok().flatten() {
+        Some(ordering) if ordering == wanted => Ok(a.clone()),
+        Some(_) => Ok(b.clone()),
+        // A NaN on either side
+        None => Ok(Object::Num(f64::NAN)),
+    }
+}
+
+// Integers raised to whole powers stay exact, unless the result would be
+// too big to be worth computing; those are left to floating point
+fn exact_pow(base: BigInt, exponent: i64) -> Option<BigInt> {
+    const MAX_BITS: u64 = 1 << 16;
+    let exponent = u32::try_from(exponent).ok()?;
+    if base.bits().saturating_mul(u64::from(exponent)) > MAX_BITS {
+        return None;
+    }
+    Some(base.pow(exponent))
+}
+
+fn unary(f: fn(f64) -> f64) -> impl Fn(&mut Random, &[Object]) -> Result<Object, LoxError> {
+    move |_, args| Ok(f(number(&args[0])?).into_lox())
+}
+
+// The `Math` global: constants and functions on numbers
+pub(crate) fn namespace() -> Object {
+    let class = UserClass::builder::<Random>("Math")
+        .getter("PI", |_| std::f64::consts::PI.into_lox())
+        .getter("E", |_| std::f64::consts::E.into_lox())
+        .method("sqrt", 1, unary(f64::sqrt))
+        .method("sin", 1, unary(f64::sin))
+        .method("cos", 1, unary(f64::cos))
+        .method("tan", 1, unary(f64::tan))
+        .method("log", 1, unary(f64::ln))
+        .method("exp", 1, unary(f64::exp))
+        .method("floor", 1, |_, args| rounded(&args[0], f64::floor))
+        .method("ceil", 1, |_, args| rounded(&args[0], f64::ceil))
+        .method("round", 1, |_, args| rounded(&args[0], f64::round))
+        .method("abs", 1, |_, args| match &args[0] {
+            Object::Int(n) => Ok(match n.checked_abs() {
+                Some(n) => Object::Int(n),
+                None => Object::from_bigint(BigInt::from(*n).abs()),
+            }),
+            Object::BigInt(n) => Ok(Object::from_bigint(n.abs())),
+            value => Ok(number(value)?.abs().into_lox()),
+        })
+        .method("pow", 2, |_, args| {
+            let exact = match (&args[0], &args[1]) {
+                (Object::Int(base), Object::Int(exponent)) => {
+                    exact_pow(BigInt::from(*base), *exponent)
+                }
+                (Object::BigInt(base), Object::Int(exponent)) => exact_pow(base.clone(), *exponent),
+                _ => None,
+            };
+            match exact {
+                Some(power) => Ok(Object::from_bigint(power)),
+                None => Ok(number(&args[0])?.powf(number(&args[1])?).into_lox()),
+            }
+        })
+        .method("min", 2, |_, args| pick(args, Ordering::Less))
+        .method("max", 2, |_, args| pick(args, Ordering::Greater))
+        .method("random", 0, |random, _| Ok(random.next_f64().into_lox()))
+        .method("seed", 1, |random, args| {
+            random.state = number(&args[0])? as i64 as u64;
+            Ok(Object::Nil)
+        })
+        .build();
+    UserData::new(&class, Random::from_clock()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lox::Lox;
+    use crate::object::Object;
+
+    fn eval(lox: &Lox, source: &str) -> String {
+        lox.eval(source).unwrap().to_string()
+    }
+
+    #[test]
+    fn functions() {
+        let lox = Lox::new();
+        assert_eq!(eval(&lox, "Math.sqrt(16) + Math.abs(-2);"), "6");
+        assert_eq!(
+            eval(&lox, "[Math.floor(2.5), Math.ceil(2.5), Math.round(-2.5)];"),
+            "[2, 3, -3]"
+        );
+        assert_eq!(
+            eval(&lox, "Math.pow(2, 100);"),
+            "1267650600228229401496703205376"
+        );
+        assert_eq!(eval(&lox, "Math.pow(4, 0.5);"), "2");
+        assert_eq!(
+            eval(&lox, "[Math.min(1, 2.5), Math.max(1, 2.5)];"),
+            "[1, 2.5]"
+        );
+        assert_eq!(
+            lox.eval("Math.PI;").unwrap(),
+            Object::Num(std::f64::consts::PI)
+        );
+        assert!(lox.eval("Math.sqrt(\"4\");").is_err());
+    }
+
+    #[test]
+    fn random_numbers_can_be_repeated() {
+        let lox = Lox::new();
+        let numbers = "Math.seed(42); [Math.random(), Math.random()];";
+        let first = eval(&lox, numbers);
+        assert_eq!(eval(&lox, numbers), first);
+        assert_ne!(
+            eval(&lox, "Math.seed(43); [Math.random(), Math.random()];"),
+            first
+        );
+
+        let n = lox.eval("Math.random();").unwrap().as_f64().unwrap();
+        assert!((0.0..1.0).contains(&n));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::error::LoxError;
+use crate::object::Object;
+use std::collections::HashMap;
+use std::fmt;
+
+// A script loaded by `import`. Each module runs in its own globals, and
+// exports the names it defines at the top level apart from those starting
+// with `_`, which stay private to it.
+pub struct Module {
+    name: String,
+    exports: HashMap<String, Object>,
+}
+
+impl Module {
+    pub fn new<'a, I>(name: &str, globals: I) -> Module
+    where
+        I: IntoIterator<Item = (&'a String, &'a Object)>,
+    {
+        let exports = globals
+            .into_iter()
+            .filter(|(name, _)| !name.starts_with('_'))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        Module {
+            name: name.to_string(),
+            exports,
+        }
+    }
+
+    // The path it was imported by
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // `m.name`
+    pub fn get(&self, name: &str) -> Result<Object, LoxError> {
+        self.exports.get(name).cloned().ok_or_else(|| {
+            LoxError::host_error(&format!("Module \"{}\" has no export '{name}'.", self.name))
+        })
+    }
+
+    // Exported names, sorted
+    pub fn exports(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.exports.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<module \"{}\">", self.name)
+    }
+}
+
+impl fmt::Debug for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Module({})", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SharedBuffer;
+    use crate::interpreter::Capabilities;
+    use crate::lox::Lox;
+    use crate::object::Object;
+    use std::fs;
+    use std::path::PathBuf;
+
+    // A fresh directory of modules for each test
+    fn modules(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lox-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (name, source) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn cycles_show_the_chain() {
+        let dir = modules(
+            "cycle",
+            &[
+                ("main.lox", "import \"a.lox\" as a;"),
+                ("a.lox", "import \"b.lox\" as b;"),
+                ("b.lox", "from \"a.lox\" import x;"),
+            ],
+        );
+        let main = dir.join("main.lox");
+        let err = Lox::new().run_file(&main).unwrap_err();
+        assert_eq!(err.message(), "Import cycle: a.lox -> b.lox -> a.lox.");
+        assert_eq!(err.line(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn modules_are_found_on_the_path() {
+        let dir = modules(
+            "path",
+            &[
+                ("lib/shapes.lox", "var sides = 4 + offset;"),
+                ("lib/offset.lox", "var offset = 0;"),
+                (
+                    "app/main.lox",
+                    "var offset = 1; from \"shapes.lox\" import sides;",
+                ),
+            ],
+        );
+        let mut lox = Lox::new();
+        let errors = SharedBuffer::new();
+        lox.set_error_output(Box::new(errors.clone()));
+        let main = dir.join("app/main.lox");
+        assert!(lox.run_file(&main).is_err());
+        assert!(errors
+            .contents()
+            .contains("Could not find module \"shapes.lox\"."));
+
+        // A module sees the built-ins and what the host defined, but not the
+        // globals of the file importing it
+        lox.interpreter_mut().set_module_path(vec![dir.join("lib")]);
+        let err = lox.run_file(&main).unwrap_err();
+        assert_eq!(err.message(), "Undefined variable 'offset'.");
+        lox.define("offset", Object::Int(2));
+        lox.run_file(&main).unwrap();
+        assert_eq!(lox.eval("sides;").unwrap(), Object::Int(6));
+
+        fs::write(
+            dir.join("lib/shapes.lox"),
+            "from \"offset.lox\" import offset; var sides = 4 + offset;",
+        )
+        .unwrap();
+        let mut lox = Lox::new();
+        lox.interpreter_mut().set_module_path(vec![dir.join("lib")]);
+        lox.run_file(&main).unwrap();
+        assert_eq!(lox.eval("sides;").unwrap(), Object::Int(4));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn modules_share_the_built_ins() {
+        let dir = modules(
+            "shared",
+            &[
+                ("main.lox", "Math.seed(7); from \"dice.lox\" import roll;"),
+                ("dice.lox", "var roll = Math.random();"),
+            ],
+        );
+        let lox = Lox::new();
+        lox.run_file(dir.join("main.lox")).unwrap();
+        let roll = lox.eval("roll;").unwrap();
+        assert_eq!(
+            Lox::new().eval("Math.seed(7); Math.random();").unwrap(),
+            roll
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn imports_stay_in_their_directory() {
+        let dir = modules(
+            "sandbox",
+            &[
+                ("secret.lox", "var key = 42;"),
+                ("app/main.lox", "from \"../secret.lox\" import key;"),
+            ],
+        );
+        let main = dir.join("app/main.lox");
+        let mut lox = Lox::new();
+        lox.set_error_output(Box::new(SharedBuffer::new()));
+        let err = lox.run_file(&main).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "Importing \"../secret.lox\" is not allowed; run with --allow-read to allow it."
+        );
+        let absolute = dir.join("secret.lox").display().to_string();
+        let err = lox
+            .eval(&format!("import \"{}\" as s;", absolute.replace('\\', "/")))
+            .unwrap_err();
+        assert!(err.message().contains("--allow-read"));
+
+        #[cfg(unix)]
+        {
+            let link = dir.join("app/link.lox");
+            std::os::unix::fs::symlink(dir.join("secret.lox"), &link).unwrap();
+            fs::write(&main, "from \"link.lox\" import key;").unwrap();
+            let err = lox.run_file(&main).unwrap_err();
+            assert!(err.message().contains("--allow-read"));
+        }
+
+        lox.interpreter_mut().set_capabilities(Capabilities::all());
+        lox.run_file(&main).unwrap();
+        assert_eq!(lox.eval("key;").unwrap(), Object::Int(42));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn uncaught_exceptions_show_the_modules() {
+        let dir = modules(
+            "trace",
+            &[
+                ("main.lox", "print 1;\nimport \"outer.lox\" as o;"),
+                ("outer.lox", "\n\nimport \"inner.lox\" as i;"),
+                ("inner.lox", "throw \"deep\";"),
+            ],
+        );
+        let (output, errors) = (SharedBuffer::new(), SharedBuffer::new());
+        let mut lox = Lox::new();
+        lox.set_output(Box::new(output.clone()));
+        lox.set_error_output(Box::new(errors.clone()));
+        let err = lox.run_file(dir.join("main.lox")).unwrap_err();
+        assert_eq!(err.value(), Some(&Object::Str("deep".to_string())));
+        assert_eq!(err.trace().collect::<Vec<_>>(), ["inner.lox", "outer.lox"]);
+        assert_eq!(
+            errors.contents(),
+            "Uncaught exception: deep\n\
+             [line 1] in inner.lox\n\
+             [line 3] in outer.lox\n\
+             [line 2] in script\n"
+        );
+
+        // An import that fails can be caught like anything else
+        lox.interpreter_mut().set_module_path(vec![dir.clone()]);
+        lox.eval("try { import \"inner.lox\" as i; } catch (e) { print e; }")
+            .unwrap();
+        assert_eq!(output.contents(), "1\n\"deep\"\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn errors_in_modules_are_reported() {
+        let dir = modules("broken", &[("broken.lox", "var a = ;")]);
+        let errors = SharedBuffer::new();
+        let mut lox = Lox::new();
+        lox.set_error_output(Box::new(errors.clone()));
+        let main = dir.join("main.lox");
+        fs::write(&main, "\nimport \"broken.lox\" as b;").unwrap();
+        assert!(lox.run_file(&main).is_err());
+        assert_eq!(
+            errors.contents(),
+            "[line 1] Error at ';': Expect expression.\n\
+             [line 2] Error: Could not compile module \"broken.lox\".\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+use crate::error::LoxError;
+use crate::object::Object;
+use std::fmt;
+use std::rc::Rc;
+
+pub type NativeFn = dyn Fn(&[Object]) -> Result<Object, LoxError>;
+
+// A function implemented by the host and bound to a global name. The
+// interpreter checks the arity before calling it, so the closure can index
+// its arguments freely.
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, function: F) -> NativeFunction
+    where
+        F: Fn(&[Object]) -> Result<Object, LoxError> + 'static,
+    {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            function: Box::new(function),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn call(&self, arguments: &[Object]) -> Result<Object, LoxError> {
+        (self.function)(arguments)
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NativeFunction({}/{})", self.name, self.arity)
+    }
+}
+
+impl From<NativeFunction> for Object {
+    fn from(function: NativeFunction) -> Object {
+        Object::NativeFunction(Rc::new(function))
+    }
+}
@@ -1,24 +1,182 @@
+use crate::error::RuntimeError;
+use crate::list::List;
+use crate::map::Map;
+use crate::module::Module;
+use crate::native::NativeFunction;
+use crate::userdata::UserData;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{ToPrimitive, Zero};
 use std::cmp::*;
 use std::fmt;
 use std::ops::*;
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub enum Object {
     Num(f64),
+    Int(i64),
+    BigInt(BigInt),
     Str(String),
     Bool(bool),
     Nil,
-    ArithmeticError,
+    NativeFunction(Rc<NativeFunction>),
+    UserData(UserData),
+    List(List),
+    Map(Map),
+    Module(Rc<Module>),
 }
 
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Object::Num(n) => write!(f, "{n}"),
+            Object::Int(n) => write!(f, "{n}"),
+            Object::BigInt(n) => write!(f, "{n}"),
             Object::Str(s) => write!(f, "\"{s}\""),
             Object::Bool(b) => write!(f, "{b}"),
             Object::Nil => write!(f, "Nil"),
-            Object::ArithmeticError => panic!("Can't print ArithmeticErrors"),
+            Object::NativeFunction(_) => write!(f, "<native fn>"),
+            Object::UserData(u) => write!(f, "{} instance", u.class().name()),
+            Object::List(l) => write!(f, "{l}"),
+            Object::Map(m) => write!(f, "{m}"),
+            Object::Module(m) => write!(f, "{m}"),
+        }
+    }
+}
+
+impl Object {
+    // Integers that have grown past i64 shrink back once they fit again
+    pub fn from_bigint(n: BigInt) -> Object {
+        match n.to_i64() {
+            Some(n) => Object::Int(n),
+            None => Object::BigInt(n),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Object::Num(n) => Some(*n),
+            Object::Int(n) => Some(*n as f64),
+            Object::BigInt(n) => n.to_f64(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bigint(&self) -> Option<BigInt> {
+        match self {
+            Object::Int(n) => Some(BigInt::from(*n)),
+            Object::BigInt(n) => Some(n.clone()),
+            _ => None,
+        }
+    }
+
+    // Integers of either size are one type as far as scripts are concerned
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Num(_) => "float",
+            Object::Int(_) | Object::BigInt(_) => "int",
+            Object::Str(_) => "string",
+            Object::Bool(_) => "bool",
+            Object::Nil => "nil",
+            Object::NativeFunction(_) => "function",
+            Object::UserData(_) => "instance",
+            Object::List(_) => "list",
+            Object::Map(_) => "map",
+            Object::Module(_) => "module",
+        }
+    }
+}
+
+//
+// Numeric tower
+//
+// A pair of numeric operands widened to the narrowest representation that
+// holds both: integers stay exact, and anything mixed with a float is a float.
+enum Operands {
+    Int(i64, i64),
+    Big(BigInt, BigInt),
+    Float(f64, f64),
+}
+
+#[derive(Copy, Clone)]
+enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    FloorDiv,
+    Rem,
+}
+
+impl Operands {
+    fn new(left: &Object, right: &Object) -> Option<Operands> {
+        match (left, right) {
+            (Object::Int(a), Object::Int(b)) => Some(Operands::Int(*a, *b)),
+            (Object::Num(a), _) => Some(Operands::Float(*a, right.as_f64()?)),
+            (_, Object::Num(b)) => Some(Operands::Float(left.as_f64()?, *b)),
+            _ => Some(Operands::Big(left.as_bigint()?, right.as_bigint()?)),
+        }
+    }
+
+    fn apply(self, op: Arithmetic) -> Result<Object, RuntimeError> {
+        // `/` always produces a float, but the other integer divisions have no
+        // infinity to fall back on
+        let exact_division = matches!(op, Arithmetic::FloorDiv | Arithmetic::Rem);
+        let zero_divisor = match &self {
+            Operands::Int(_, n) => *n == 0,
+            Operands::Big(_, n) => n.is_zero(),
+            Operands::Float(_, _) => false,
+        };
+        if exact_division && zero_divisor {
+            return Err(RuntimeError::new("Division by zero."));
+        }
+
+        Ok(match self {
+            Operands::Int(left, right) => match Operands::int_arithmetic(op, left, right) {
+                Some(result) => result,
+                // Overflowed, so redo it with bignums
+                None => Operands::big_arithmetic(op, BigInt::from(left), BigInt::from(right)),
+            },
+            Operands::Big(left, right) => Operands::big_arithmetic(op, left, right),
+            Operands::Float(left, right) => Object::Num(match op {
+                Arithmetic::Add => left + right,
+                Arithmetic::Sub => left - right,
+                Arithmetic::Mul => left * right,
+                Arithmetic::Div => left / right,
+                Arithmetic::FloorDiv => (left / right).floor(),
+                Arithmetic::Rem => left - right * (left / right).floor(),
+            }),
+        })
+    }
+
+    // Returns None when the result doesn't fit in an i64
+    fn int_arithmetic(op: Arithmetic, left: i64, right: i64) -> Option<Object> {
+        let result = match op {
+            Arithmetic::Add => left.checked_add(right)?,
+            Arithmetic::Sub => left.checked_sub(right)?,
+            Arithmetic::Mul => left.checked_mul(right)?,
+            Arithmetic::Div => return Some(Object::Num(left as f64 / right as f64)),
+            Arithmetic::FloorDiv if right == -1 => left.checked_neg()?,
+            Arithmetic::FloorDiv => Integer::div_floor(&left, &right),
+            Arithmetic::Rem if right == -1 => 0,
+            Arithmetic::Rem => Integer::mod_floor(&left, &right),
+        };
+        Some(Object::Int(result))
+    }
+
+    fn big_arithmetic(op: Arithmetic, left: BigInt, right: BigInt) -> Object {
+        match op {
+            Arithmetic::Add => Object::from_bigint(left + right),
+            Arithmetic::Sub => Object::from_bigint(left - right),
+            Arithmetic::Mul => Object::from_bigint(left * right),
+            Arithmetic::Div => {
+                let left = left.to_f64().unwrap_or(f64::NAN);
+                let right = right.to_f64().unwrap_or(f64::NAN);
+                Object::Num(left / right)
+            }
+            Arithmetic::FloorDiv => Object::from_bigint(left.div_floor(&right)),
+            Arithmetic::Rem => Object::from_bigint(left.mod_floor(&right)),
         }
     }
 }
@@ -27,12 +185,17 @@ impl fmt::Display for Object {
 // Unary Operations
 //
 impl Neg for Object {
-    type Output = Object;
+    type Output = Result<Object, RuntimeError>;
 
-    fn neg(self) -> Object {
+    fn neg(self) -> Result<Object, RuntimeError> {
         match self {
-            Object::Num(n) => Object::Num(-n),
-            _ => Object::ArithmeticError,
+            Object::Num(n) => Ok(Object::Num(-n)),
+            Object::Int(n) => Ok(match n.checked_neg() {
+                Some(n) => Object::Int(n),
+                None => Object::from_bigint(-BigInt::from(n)),
+            }),
+            Object::BigInt(n) => Ok(Object::from_bigint(-n)),
+            _ => Err(RuntimeError::operand("Operand must be a number.", &self)),
         }
     }
 }
@@ -43,23 +206,115 @@ impl Not for Object {
     fn not(self) -> Object {
         match self {
             Object::Nil | Object::Bool(false) => Object::Bool(true),
-            Object::Num(_) | Object::Str(_) | Object::Bool(true) => Object::Bool(false),
-            _ => Object::ArithmeticError,
+            Object::Num(_)
+            | Object::Int(_)
+            | Object::BigInt(_)
+            | Object::Str(_)
+            | Object::Bool(true)
+            | Object::NativeFunction(_)
+            | Object::UserData(_)
+            | Object::List(_)
+            | Object::Map(_)
+            | Object::Module(_) => Object::Bool(false),
         }
     }
 }
 
+//
+// Binary Operations
+//
+impl Object {
+    fn arithmetic(self, op: Arithmetic, rhs: Object) -> Result<Object, RuntimeError> {
+        match Operands::new(&self, &rhs) {
+            Some(operands) => operands.apply(op),
+            None => Err(RuntimeError::operands(
+                "Operands must be numbers.",
+                &self,
+                &rhs,
+            )),
+        }
+    }
+
+    pub fn floor_div(self, rhs: Object) -> Result<Object, RuntimeError> {
+        self.arithmetic(Arithmetic::FloorDiv, rhs)
+    }
+
+    // The ordering is None when the operands are unordered, i.e. NaN
+    pub fn compare(&self, other: &Object) -> Result<Option<Ordering>, RuntimeError> {
+        match (self, other) {
+            (Object::Str(s1), Object::Str(s2)) => Ok(s1.partial_cmp(s2)),
+            _ => match Operands::new(self, other) {
+                Some(Operands::Int(n1, n2)) => Ok(n1.partial_cmp(&n2)),
+                Some(Operands::Big(n1, n2)) => Ok(n1.partial_cmp(&n2)),
+                Some(Operands::Float(n1, n2)) => Ok(n1.partial_cmp(&n2)),
+                None => Err(RuntimeError::operands(
+                    "Operands must be two numbers or two strings.",
+                    self,
+                    other,
+                )),
+            },
+        }
+    }
+}
+
+impl Add for Object {
+    type Output = Result<Object, RuntimeError>;
+
+    fn add(self, rhs: Object) -> Result<Object, RuntimeError> {
+        match (self, rhs) {
+            (Object::Str(s1), Object::Str(s2)) => Ok(Object::Str(s1 + &s2)),
+            (left, right) => match Operands::new(&left, &right) {
+                Some(operands) => operands.apply(Arithmetic::Add),
+                None => Err(RuntimeError::operands(
+                    "Operands must be two numbers or two strings.",
+                    &left,
+                    &right,
+                )),
+            },
+        }
+    }
+}
+
+impl Sub for Object {
+    type Output = Result<Object, RuntimeError>;
+
+    fn sub(self, rhs: Object) -> Result<Object, RuntimeError> {
+        self.arithmetic(Arithmetic::Sub, rhs)
+    }
+}
+
+impl Mul for Object {
+    type Output = Result<Object, RuntimeError>;
+
+    fn mul(self, rhs: Object) -> Result<Object, RuntimeError> {
+        self.arithmetic(Arithmetic::Mul, rhs)
+    }
+}
+
+impl Div for Object {
+    type Output = Result<Object, RuntimeError>;
+
+    fn div(self, rhs: Object) -> Result<Object, RuntimeError> {
+        self.arithmetic(Arithmetic::Div, rhs)
+    }
+}
+
+impl Rem for Object {
+    type Output = Result<Object, RuntimeError>;
+
+    fn rem(self, rhs: Object) -> Result<Object, RuntimeError> {
+        self.arithmetic(Arithmetic::Rem, rhs)
+    }
+}
+
 //
 // Comparisons
 //
+// Only what `<` and friends accept is ordered, so host code can't order
+// values that scripts can't
 impl PartialOrd for Object {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (self, other) {
-            (Object::Num(n1), Object::Num(n2)) => n1.partial_cmp(n2),
-            (Object::Str(s1), Object::Str(s2)) => s1.partial_cmp(s2),
-            (Object::Bool(b1), Object::Bool(b2)) => b1.partial_cmp(b2),
-            _ => None,
-        }
+        self.compare(other).ok().flatten()
     }
 }
 
@@ -68,12 +323,71 @@ impl PartialOrd for Object {
 //
 impl PartialEq for Object {
     fn eq(&self, other: &Self) -> bool {
+        if let Some(operands) = Operands::new(self, other) {
+            return match operands {
+                Operands::Int(n1, n2) => n1 == n2,
+                Operands::Big(n1, n2) => n1 == n2,
+                Operands::Float(n1, n2) => n1 == n2,
+            };
+        }
         match (self, other) {
-            (Object::Num(n1), Object::Num(n2)) => n1 == n2,
             (Object::Str(s1), Object::Str(s2)) => s1 == s2,
             (Object::Bool(b1), Object::Bool(b2)) => b1 == b2,
             (Object::Nil, Object::Nil) => true,
+            (Object::NativeFunction(f1), Object::NativeFunction(f2)) => Rc::ptr_eq(f1, f2),
+            (Object::UserData(u1), Object::UserData(u2)) => u1.same_instance(u2),
+            (Object::List(l1), Object::List(l2)) => l1.same_instance(l2),
+            (Object::Map(m1), Object::Map(m2)) => m1.same_instance(m2),
+            (Object::Module(m1), Object::Module(m2)) => Rc::ptr_eq(m1, m2),
             _ => false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operand_errors_name_the_types() {
+        let err = (-Object::Str("abc".to_string())).unwrap_err();
+        assert_eq!(err.to_string(), "Operand must be a number. Got string.");
+
+        let err = (Object::Int(1) - Object::Nil).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Operands must be numbers. Got int and nil."
+        );
+    }
+
+    #[test]
+    fn plus_accepts_numbers_or_strings() {
+        let err = (Object::Str("abc".to_string()) + Object::Num(1.0)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Operands must be two numbers or two strings. Got string and float."
+        );
+
+        let err = (Object::Bool(true) + Object::Bool(false)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Operands must be two numbers or two strings. Got bool and bool."
+        );
+    }
+
+    #[test]
+    fn only_numbers_and_strings_are_ordered() {
+        assert!(Object::Int(1) < Object::Num(1.5));
+        assert!(Object::Str("a".to_string()) < Object::Str("b".to_string()));
+        assert_eq!(Object::Bool(true).partial_cmp(&Object::Bool(false)), None);
+        assert_eq!(Object::Nil.partial_cmp(&Object::Nil), None);
+    }
+
+    #[test]
+    fn integer_division_by_zero() {
+        let err = Object::Int(1).floor_div(Object::Int(0)).unwrap_err();
+        assert_eq!(err.to_string(), "Division by zero.");
+        assert!((Object::Int(1) % Object::Int(0)).is_err());
+        assert!((Object::Num(1.0) / Object::Int(0)).is_ok());
+    }
+}
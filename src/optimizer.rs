@@ -0,0 +1,154 @@
+use crate::error::LoxError;
+use crate::expr::*;
+use crate::interpreter::Interpreter;
+use crate::stmt::*;
+
+// Folds constant subtrees into literals before the tree is interpreted.
+// Constant operands are evaluated by the interpreter itself so folding can
+// never disagree with what would have happened at runtime. An operation that
+// is bound to fail (e.g. `-"str"`) is left as it is, so that it fails when it
+// runs, after whatever comes before it, and can be caught.
+//
+// Algebraic identities such as `x * 1` are deliberately left alone: until the
+// operand is known to be a number, rewriting it would hide a type error.
+pub struct ConstantFolder<'a> {
+    interpreter: &'a Interpreter,
+}
+
+impl<'a> ConstantFolder<'a> {
+    pub fn new(interpreter: &'a Interpreter) -> ConstantFolder<'a> {
+        ConstantFolder { interpreter }
+    }
+
+    pub fn optimize(&mut self, statements: Vec<Stmt>) -> Result<Vec<Stmt>, LoxError> {
+        statements
+            .into_iter()
+            .map(|stmt| self.fold_stmt(stmt))
+            .collect()
+    }
+
+    fn fold_stmt(&mut self, stmt: Stmt) -> Result<Stmt, LoxError> {
+        Ok(match stmt {
+            Stmt::Block(s) => Stmt::Block(BlockStmt {
+                statements: self.optimize(s.statements)?,
+            }),
+            Stmt::Expression(s) => Stmt::Expression(ExpressionStmt {
+                expression: self.fold_expr(s.expression)?,
+            }),
+            Stmt::FromImport(_) | Stmt::Import(_) => stmt,
+            Stmt::Print(s) => Stmt::Print(PrintStmt {
+                expression: self.fold_expr(s.expression)?,
+            }),
+            Stmt::Throw(s) => Stmt::Throw(ThrowStmt {
+                keyword: s.keyword,
+                value: self.fold_expr(s.value)?,
+            }),
+            Stmt::Try(s) => Stmt::Try(TryStmt {
+                keyword: s.keyword,
+                body: self.optimize(s.body)?,
+                name: s.name,
+                handler: self.optimize(s.handler)?,
+                finalizer: s.finalizer.map(|f| self.optimize(f)).transpose()?,
+            }),
+            Stmt::Var(s) => Stmt::Var(VarStmt {
+                name: s.name,
+                initializer: s.initializer.map(|e| self.fold_expr(e)).transpose()?,
+            }),
+        })
+    }
+
+    fn evaluate(&self, expr: Expr) -> Result<Expr, LoxError> {
+        match expr.accept(self.interpreter) {
+            Ok(value) => Ok(Expr::Literal(LiteralExpr { value: Some(value) })),
+            Err(_) => Ok(expr),
+        }
+    }
+
+    fn is_constant(expr: &Expr) -> bool {
+        matches!(expr, Expr::Literal(_))
+    }
+}
+
+impl Fold for ConstantFolder<'_> {
+    fn fold_binary_expr(&mut self, expr: BinaryExpr) -> Result<Expr, LoxError> {
+        match fold_binary_expr(self, expr)? {
+            Expr::Binary(b) if Self::is_constant(&b.left) && Self::is_constant(&b.right) => {
+                self.evaluate(Expr::Binary(b))
+            }
+            folded => Ok(folded),
+        }
+    }
+
+    // The tree already encodes precedence, so a grouping is just its contents
+    fn fold_grouping_expr(&mut self, expr: GroupingExpr) -> Result<Expr, LoxError> {
+        self.fold_expr(*expr.expression)
+    }
+
+    fn fold_unary_expr(&mut self, expr: UnaryExpr) -> Result<Expr, LoxError> {
+        match fold_unary_expr(self, expr)? {
+            Expr::Unary(u) if Self::is_constant(&u.right) => self.evaluate(Expr::Unary(u)),
+            folded => Ok(folded),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_printer::AstPrinter;
+    use crate::interpreter::NumericPolicy;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    fn optimize_in(interpreter: &Interpreter, source: &str) -> Result<String, LoxError> {
+        let mut scanner = Scanner::new(format!("{source};"));
+        let tokens = scanner.scan_tokens()?;
+        let statements = Parser::new(tokens)
+            .parse()
+            .expect("test source should parse");
+        match ConstantFolder::new(interpreter)
+            .optimize(statements)?
+            .as_slice()
+        {
+            [Stmt::Expression(s)] => AstPrinter {}.print(&s.expression),
+            _ => panic!("expected a single expression statement"),
+        }
+    }
+
+    fn optimize(source: &str) -> Result<String, LoxError> {
+        optimize_in(&Interpreter::new(), source)
+    }
+
+    #[test]
+    fn folds_arithmetic() {
+        assert_eq!(optimize("(1 + 2) * 3").ok(), Some("9".to_string()));
+        assert_eq!(optimize("-(4 - 6) / 2").ok(), Some("1".to_string()));
+    }
+
+    #[test]
+    fn folds_strings_and_comparisons() {
+        assert_eq!(optimize("\"a\" + \"b\"").ok(), Some("\"ab\"".to_string()));
+        assert_eq!(optimize("!(1 < 2) == false").ok(), Some("true".to_string()));
+    }
+
+    #[test]
+    fn removes_groupings() {
+        assert_eq!(optimize("((((nil))))").ok(), Some("Nil".to_string()));
+    }
+
+    #[test]
+    fn leaves_failures_for_the_runtime() {
+        assert_eq!(optimize("-\"str\"").ok(), Some("(- \"str\")".to_string()));
+        assert_eq!(
+            optimize("1 + (2 * true)").ok(),
+            Some("(+ 1 (* 2 true))".to_string())
+        );
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_numeric_policy(NumericPolicy::ErrorOnZeroDivision);
+        assert_eq!(
+            optimize_in(&interpreter, "1 / 0.0").ok(),
+            Some("(/ 1 0)".to_string())
+        );
+    }
+}
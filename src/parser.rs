@@ -1,28 +1,255 @@
 use crate::error::LoxError;
 use crate::expr::*;
 use crate::object::Object;
+use crate::stmt::*;
 use crate::token::*;
 use crate::token_type::TokenType;
 
+// How deeply expressions and blocks can nest, so that parsing them, and
+// everything that walks the tree afterwards, doesn't overflow the stack
+const MAX_DEPTH: usize = 64;
+
 pub struct Parser<'a> {
     tokens: &'a Vec<Token>,
     current: usize,
+    errors: Vec<LoxError>,
+    bare_expression: bool,
+    // How many expressions and blocks enclose the current token
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(tokens: &Vec<Token>) -> Parser {
-        Parser { tokens, current: 0 }
+    pub fn new(tokens: &Vec<Token>) -> Parser<'_> {
+        Parser {
+            tokens,
+            current: 0,
+            errors: Vec::new(),
+            bare_expression: false,
+            depth: 0,
+        }
+    }
+
+    // Lets the source end in an expression with no `;`, as typed at a prompt
+    pub fn set_bare_expression(&mut self, allowed: bool) {
+        self.bare_expression = allowed;
+    }
+
+    // Recovers from errors at statement boundaries so that every error can be
+    // reported; the last one is returned and `errors` has them all.
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, LoxError> {
+        let mut statements = Vec::new();
+        while !self.is_at_end() {
+            match self.declaration() {
+                Ok(stmt) => statements.push(stmt),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.synchronize();
+                }
+            }
+        }
+
+        match self.errors.last() {
+            None => Ok(statements),
+            Some(e) => Err(e.clone()),
+        }
+    }
+
+    pub fn errors(&self) -> &[LoxError] {
+        &self.errors
     }
 
-    pub fn parse(&mut self) -> Option<Expr> {
-        match self.expression() {
-            Ok(expr) => Some(expr),
-            _ => None,
+    fn declaration(&mut self) -> Result<Stmt, LoxError> {
+        if self.is_match(&[TokenType::Var]) {
+            return self.var_declaration();
         }
+        if self.is_match(&[TokenType::Import]) {
+            return self.import_declaration();
+        }
+        if self.is_match(&[TokenType::From]) {
+            return self.import_names_declaration();
+        }
+        self.statement()
+    }
+
+    // `import "path" as name;`
+    fn import_declaration(&mut self) -> Result<Stmt, LoxError> {
+        let keyword = self.previous().dup();
+        let path = self.consume(TokenType::String, "Expect module path after 'import'.")?;
+        self.consume(TokenType::As, "Expect 'as' after module path.")?;
+        let name = self.consume(TokenType::Identifier, "Expect module name after 'as'.")?;
+        self.consume(TokenType::Semicolon, "Expect ';' after import.")?;
+        Ok(Stmt::Import(ImportStmt {
+            keyword,
+            path,
+            name,
+        }))
+    }
+
+    // `from "path" import a, b;`
+    fn import_names_declaration(&mut self) -> Result<Stmt, LoxError> {
+        let keyword = self.previous().dup();
+        let path = self.consume(TokenType::String, "Expect module path after 'from'.")?;
+        self.consume(TokenType::Import, "Expect 'import' after module path.")?;
+        let mut names = vec![self.consume(TokenType::Identifier, "Expect name to import.")?];
+        while self.is_match(&[TokenType::Comma]) {
+            names.push(self.consume(TokenType::Identifier, "Expect name to import.")?);
+        }
+        self.consume(TokenType::Semicolon, "Expect ';' after import.")?;
+        Ok(Stmt::FromImport(FromImportStmt {
+            keyword,
+            path,
+            names,
+        }))
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt, LoxError> {
+        let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
+        let initializer = if self.is_match(&[TokenType::Equal]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after variable declaration.",
+        )?;
+        Ok(Stmt::Var(VarStmt { name, initializer }))
+    }
+
+    fn statement(&mut self) -> Result<Stmt, LoxError> {
+        if self.is_match(&[TokenType::Print]) {
+            return self.print_statement();
+        }
+        if self.is_match(&[TokenType::Throw]) {
+            return self.throw_statement();
+        }
+        if self.is_match(&[TokenType::Try]) {
+            return self.try_statement();
+        }
+        if self.check(TokenType::LeftBrace) && !self.starts_map() {
+            self.advance();
+            return Ok(Stmt::Block(BlockStmt {
+                statements: self.block()?,
+            }));
+        }
+        self.expression_statement()
+    }
+
+    // A statement starting with `{` is a block unless it starts a map with a
+    // simple key, e.g. `{"a": 1}`; any other map can go in parentheses.
+    fn starts_map(&self) -> bool {
+        self.tokens
+            .get(self.current + 2)
+            .is_some_and(|token| token.is(TokenType::Colon))
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, LoxError> {
+        self.nested("Block nested too deeply.", |parser| {
+            let mut statements = Vec::new();
+            while !parser.check(TokenType::RightBrace) && !parser.is_at_end() {
+                statements.push(parser.declaration()?);
+            }
+            parser.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+            Ok(statements)
+        })
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt, LoxError> {
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+        Ok(Stmt::Print(PrintStmt { expression: value }))
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt, LoxError> {
+        let keyword = self.previous().dup();
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after thrown value.")?;
+        Ok(Stmt::Throw(ThrowStmt { keyword, value }))
+    }
+
+    // `try { } catch (e) { } finally { }`, where either clause can be left
+    // out but not both
+    fn try_statement(&mut self) -> Result<Stmt, LoxError> {
+        let keyword = self.previous().dup();
+        self.consume(TokenType::LeftBrace, "Expect '{' after 'try'.")?;
+        let body = self.block()?;
+
+        let (name, handler) = if self.is_match(&[TokenType::Catch]) {
+            self.consume(TokenType::LeftParen, "Expect '(' after 'catch'.")?;
+            let name = self.consume(TokenType::Identifier, "Expect name of caught value.")?;
+            self.consume(TokenType::RightParen, "Expect ')' after caught value.")?;
+            self.consume(TokenType::LeftBrace, "Expect '{' after catch clause.")?;
+            (Some(name), self.block()?)
+        } else {
+            (None, Vec::new())
+        };
+        let finalizer = if self.is_match(&[TokenType::Finally]) {
+            self.consume(TokenType::LeftBrace, "Expect '{' after 'finally'.")?;
+            Some(self.block()?)
+        } else {
+            None
+        };
+        if name.is_none() && finalizer.is_none() {
+            return Err(LoxError::parse_error(
+                self.peek(),
+                "Expect 'catch' or 'finally' after try block.",
+            ));
+        }
+        Ok(Stmt::Try(TryStmt {
+            keyword,
+            body,
+            name,
+            handler,
+            finalizer,
+        }))
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt, LoxError> {
+        let expr = self.expression()?;
+        if self.bare_expression && self.is_at_end() {
+            return Ok(Stmt::Expression(ExpressionStmt { expression: expr }));
+        }
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        Ok(Stmt::Expression(ExpressionStmt { expression: expr }))
     }
 
     fn expression(&mut self) -> Result<Expr, LoxError> {
-        self.equality()
+        self.nested("Expression nested too deeply.", Parser::assignment)
+    }
+
+    fn assignment(&mut self) -> Result<Expr, LoxError> {
+        let expr = self.equality()?;
+
+        if self.is_match(&[TokenType::Equal]) {
+            let equals = self.previous().dup();
+            let value = self.expression()?;
+
+            return match expr {
+                Expr::Variable(variable) => Ok(Expr::Assign(AssignExpr {
+                    name: variable.name,
+                    value: Box::new(value),
+                })),
+                Expr::Get(get) => Ok(Expr::Set(SetExpr {
+                    object: get.object,
+                    name: get.name,
+                    value: Box::new(value),
+                })),
+                Expr::Index(index) => Ok(Expr::SetIndex(SetIndexExpr {
+                    object: index.object,
+                    bracket: index.bracket,
+                    index: index.index,
+                    value: Box::new(value),
+                })),
+                _ => {
+                    // Recorded without giving up on the statement
+                    self.errors
+                        .push(LoxError::parse_error(&equals, "Invalid assignment target."));
+                    Ok(expr)
+                }
+            };
+        }
+
+        Ok(expr)
     }
 
     fn equality(&mut self) -> Result<Expr, LoxError> {
@@ -81,7 +308,12 @@ impl<'a> Parser<'a> {
     fn factor(&mut self) -> Result<Expr, LoxError> {
         let mut expr: Expr = self.unary()?;
 
-        while self.is_match(&[TokenType::Slash, TokenType::Star]) {
+        while self.is_match(&[
+            TokenType::Slash,
+            TokenType::Star,
+            TokenType::TildeSlash,
+            TokenType::Percent,
+        ]) {
             let operator: Token = self.previous().dup();
             let right: Expr = self.unary()?;
             expr = Expr::Binary(BinaryExpr {
@@ -97,13 +329,67 @@ impl<'a> Parser<'a> {
     fn unary(&mut self) -> Result<Expr, LoxError> {
         if self.is_match(&[TokenType::Bang, TokenType::Minus]) {
             let operator: Token = self.previous().dup();
-            let right: Expr = self.unary()?;
+            let right: Expr = self.nested("Expression nested too deeply.", Parser::unary)?;
             return Ok(Expr::Unary(UnaryExpr {
                 operator,
                 right: Box::new(right),
             }));
         }
-        self.primary()
+        self.call()
+    }
+
+    fn call(&mut self) -> Result<Expr, LoxError> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.is_match(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.is_match(&[TokenType::Dot]) {
+                let name =
+                    self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+                expr = Expr::Get(GetExpr {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else if self.is_match(&[TokenType::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = Expr::Index(IndexExpr {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                });
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, LoxError> {
+        let mut arguments = Vec::new();
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if arguments.len() >= 255 {
+                    // Recorded without giving up on the call
+                    let err =
+                        LoxError::parse_error(self.peek(), "Can't have more than 255 arguments.");
+                    self.errors.push(err);
+                }
+                arguments.push(self.expression()?);
+                if !self.is_match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+
+        Ok(Expr::Call(CallExpr {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        }))
     }
 
     fn primary(&mut self) -> Result<Expr, LoxError> {
@@ -129,15 +415,95 @@ impl<'a> Parser<'a> {
             }));
         }
 
+        if self.is_match(&[TokenType::Interpolation]) {
+            return self.interpolation();
+        }
+
+        if self.is_match(&[TokenType::Identifier]) {
+            return Ok(Expr::Variable(VariableExpr {
+                name: self.previous().dup(),
+            }));
+        }
+
+        if self.is_match(&[TokenType::LeftBracket]) {
+            return self.list();
+        }
+
+        if self.is_match(&[TokenType::LeftBrace]) {
+            return self.map();
+        }
+
         if self.is_match(&[TokenType::LeftParen]) {
             let expr: Expr = self.expression()?;
-            self.consume(TokenType::RightParen, "Expect ')' after expression")?;
+            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
             return Ok(Expr::Grouping(GroupingExpr {
                 expression: Box::new(expr),
             }));
         }
 
-        Err(LoxError::error(self.previous().line, "Expect expression."))
+        Err(LoxError::parse_error(self.peek(), "Expect expression."))
+    }
+
+    // The parts alternate between the string's text and the expressions
+    // interpolated into it, starting and ending with text
+    fn interpolation(&mut self) -> Result<Expr, LoxError> {
+        let string = self.previous().dup();
+        let mut parts = vec![self.segment()];
+        loop {
+            parts.push(self.expression()?);
+            if self.is_match(&[TokenType::Interpolation]) {
+                parts.push(self.segment());
+            } else {
+                self.consume(
+                    TokenType::String,
+                    "Expect '}' after interpolated expression.",
+                )?;
+                parts.push(self.segment());
+                break;
+            }
+        }
+        Ok(Expr::Interpolate(InterpolateExpr { string, parts }))
+    }
+
+    fn segment(&self) -> Expr {
+        Expr::Literal(LiteralExpr {
+            value: self.previous().literal.clone(),
+        })
+    }
+
+    fn list(&mut self) -> Result<Expr, LoxError> {
+        let mut elements = Vec::new();
+        if !self.check(TokenType::RightBracket) {
+            loop {
+                elements.push(self.expression()?);
+                if !self.is_match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        let bracket = self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+        Ok(Expr::List(ListExpr { bracket, elements }))
+    }
+
+    fn map(&mut self) -> Result<Expr, LoxError> {
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        if !self.check(TokenType::RightBrace) {
+            loop {
+                keys.push(self.expression()?);
+                self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+                values.push(self.expression()?);
+                if !self.is_match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        let brace = self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+        Ok(Expr::Map(MapExpr {
+            brace,
+            keys,
+            values,
+        }))
     }
 
     fn is_match(&mut self, types: &[TokenType]) -> bool {
@@ -150,6 +516,20 @@ impl<'a> Parser<'a> {
         false
     }
 
+    fn nested<T>(
+        &mut self,
+        message: &str,
+        parse: impl FnOnce(&mut Parser<'a>) -> Result<T, LoxError>,
+    ) -> Result<T, LoxError> {
+        if self.depth == MAX_DEPTH {
+            return Err(LoxError::parse_error(self.peek(), message));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
     fn error(token: Token, message: &str) -> Result<Token, LoxError> {
         Err(LoxError::parse_error(&token, message))
     }
@@ -172,6 +552,10 @@ impl<'a> Parser<'a> {
                     | TokenType::While
                     | TokenType::Print
                     | TokenType::Return
+                    | TokenType::Import
+                    | TokenType::From
+                    | TokenType::Throw
+                    | TokenType::Try
             ) {
                 return;
             }
@@ -196,8 +580,9 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // The scanner always ends the tokens with Eof, and nothing advances past it
     fn peek(&self) -> &Token {
-        self.tokens.get(self.current).unwrap()
+        &self.tokens[self.current]
     }
 
     fn is_at_end(&self) -> bool {
@@ -212,6 +597,38 @@ impl<'a> Parser<'a> {
     }
 
     fn previous(&self) -> &Token {
-        return self.tokens.get(self.current - 1).unwrap();
+        // Before anything has been consumed, the current token stands in
+        &self.tokens[self.current.saturating_sub(1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    fn parse(source: &str) -> Result<Vec<Stmt>, LoxError> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens()?.clone();
+        let result = Parser::new(&tokens).parse();
+        result
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let parens = format!("print {}1{};", "(".repeat(20_000), ")".repeat(20_000));
+        let err = parse(&parens).unwrap_err();
+        assert_eq!(err.message(), "Expression nested too deeply.");
+
+        let negations = format!("print {}1;", "-".repeat(50_000));
+        let err = parse(&negations).unwrap_err();
+        assert_eq!(err.message(), "Expression nested too deeply.");
+
+        let blocks = format!("{}{}", "{".repeat(20_000), "}".repeat(20_000));
+        let err = parse(&blocks).unwrap_err();
+        assert_eq!(err.message(), "Block nested too deeply.");
+
+        let shallow = format!("print {}1{};", "(".repeat(50), ")".repeat(50));
+        assert!(parse(&shallow).is_ok());
     }
 }
@@ -0,0 +1,203 @@
+use lox_ast::ast_printer::AstPrinter;
+use lox_ast::scanner::KEYWORDS;
+use lox_ast::{ErrorKind, Lox, LoxError};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Instant;
+
+const HELP: &str = "\
+Enter Lox statements, or an expression to see its value.
+  :help          show this message
+  :tokens <src>  show the tokens scanned from <src>
+  :ast <src>     show the tree parsed from <src>
+  :env           list the global variables
+  :load <file>   run a script in this session
+  :reset         forget every global variable
+  :time <src>    run <src> and show how long it took
+  :quit          leave (Ctrl-D also works)";
+
+// Completes keywords and global names, and keeps reading while brackets or
+// a string are left open.
+struct LoxHelper {
+    lox: Rc<Lox>,
+}
+
+impl Completer for LoxHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(0, |i| i + 1);
+        let prefix = &line[start..pos];
+        if prefix.is_empty() {
+            return Ok((pos, Vec::new()));
+        }
+
+        let globals = self.lox.interpreter().globals();
+        let mut candidates: Vec<String> = KEYWORDS
+            .iter()
+            .map(|keyword| keyword.to_string())
+            .chain(globals.into_iter().map(|(name, _)| name))
+            .filter(|word| word.starts_with(prefix))
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for LoxHelper {
+    type Hint = String;
+}
+
+impl Highlighter for LoxHelper {}
+
+impl Validator for LoxHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        if input.starts_with(':') || Lox::is_complete(input) {
+            Ok(ValidationResult::Valid(None))
+        } else {
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
+
+impl Helper for LoxHelper {}
+
+fn history_file() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".lox_history"))
+}
+
+// Returns the exit status the session ended with
+pub fn run(lox: Lox) -> rustyline::Result<i32> {
+    let lox = Rc::new(lox);
+    let mut editor: Editor<LoxHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(LoxHelper {
+        lox: Rc::clone(&lox),
+    }));
+    let history = history_file();
+    if let Some(path) = &history {
+        // There's no history yet the first time
+        let _ = editor.load_history(path);
+    }
+
+    // Input that isn't from a terminal arrives a line at a time, so open
+    // brackets are handled here as well as by the validator
+    let mut source = String::new();
+    let mut status = 0;
+    loop {
+        let prompt = if source.is_empty() { "> " } else { "... " };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                source.push_str(&line);
+                source.push('\n');
+            }
+            Err(ReadlineError::Interrupted) => {
+                source.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err),
+        }
+        if !source.starts_with(':') && !Lox::is_complete(&source) {
+            continue;
+        }
+
+        let input = std::mem::take(&mut source);
+        let input = input.trim_end();
+        if !input.is_empty() {
+            editor.add_history_entry(input)?;
+        }
+        let ended = match input.strip_prefix(':') {
+            Some(command) => meta_command(&lox, command),
+            None => eval(&lox, input),
+        };
+        if let Some(exit) = ended {
+            status = exit;
+            break;
+        }
+    }
+
+    if let Some(path) = &history {
+        editor.save_history(path)?;
+    }
+    Ok(status)
+}
+
+// Errors have already been reported and don't end the session; calling
+// `exit` does, and its status is returned
+fn eval(lox: &Lox, source: &str) -> Option<i32> {
+    let result = lox.eval_line(source).map(|value| {
+        if let Some(value) = value {
+            println!("{}", lox.interpreter().stringify(&value));
+        }
+    });
+    exit_status(result)
+}
+
+// Returns the exit status when the session should end
+fn meta_command(lox: &Lox, command: &str) -> Option<i32> {
+    let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+    let argument = argument.trim();
+    match name {
+        "help" => println!("{HELP}"),
+        "tokens" => {
+            if let Ok(tokens) = lox.scan(argument) {
+                for token in tokens {
+                    println!("{token}");
+                }
+            }
+        }
+        "ast" => {
+            if let Ok(statements) = lox.parse_line(argument) {
+                for stmt in &statements {
+                    match AstPrinter.print_stmt(stmt) {
+                        Ok(tree) => println!("{tree}"),
+                        Err(err) => lox.interpreter().report(&err),
+                    }
+                }
+            }
+        }
+        "env" => {
+            for (name, value) in lox.interpreter().globals() {
+                println!("{name} = {}", lox.interpreter().stringify(&value));
+            }
+        }
+        "load" => return exit_status(lox.run_file(argument).map(|_| ())),
+        "reset" => lox.reset(),
+        "time" => {
+            let start = Instant::now();
+            let ended = eval(lox, argument);
+            println!("{:?}", start.elapsed());
+            return ended;
+        }
+        "quit" => return Some(0),
+        _ => eprintln!("Unknown command ':{name}'; try :help."),
+    }
+    None
+}
+
+// The status a script asked to exit with, if it did
+fn exit_status(result: Result<(), LoxError>) -> Option<i32> {
+    match result {
+        Err(err) => match err.kind() {
+            ErrorKind::Exit(status) => Some(status),
+            _ => None,
+        },
+        Ok(()) => None,
+    }
+}
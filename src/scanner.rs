@@ -1,4 +1,5 @@
 use crate::error::LoxError;
+use crate::interpreter::Dialect;
 use crate::object::Object;
 use crate::token::Token;
 use crate::token_type::TokenType;
@@ -9,8 +10,22 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: usize,
+    errors: Vec<LoxError>,
+    // One entry per `${` being scanned, counting the braces opened inside it
+    // so that the `}` that ends it can be told apart
+    interpolations: Vec<usize>,
+    // The line each comment starts on; comments aren't tokens
+    comments: Vec<usize>,
+    dialect: Dialect,
 }
 
+// Every word `keyword` recognizes, e.g. for completing them at a prompt
+pub const KEYWORDS: [&str; 23] = [
+    "and", "as", "catch", "class", "else", "false", "finally", "for", "from", "fun", "if",
+    "import", "nil", "or", "print", "return", "super", "this", "throw", "true", "try", "var",
+    "while",
+];
+
 impl Scanner {
     pub fn new(source: String) -> Scanner {
         Scanner {
@@ -19,30 +34,51 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
+            errors: Vec::new(),
+            interpolations: Vec::new(),
+            comments: Vec::new(),
+            dialect: Dialect::default(),
         }
     }
 
+    // The strict dialect scans only what the reference implementation does,
+    // e.g. `${` is plain text in a string
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect = dialect;
+    }
+
+    // Scans the whole source even after an error, so that every error can be
+    // reported; the last one is returned and `errors` has them all.
     pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, LoxError> {
-        let mut had_error: Option<LoxError> = None;
         while !self.is_at_end() {
             self.start = self.current;
-            match self.scan_token() {
-                Ok(()) => {}
-                Err(e) => {
-                    e.report("");
-                    had_error = Some(e);
-                }
-            };
+            if let Err(e) = self.scan_token() {
+                self.errors.push(e);
+            }
         }
 
-        self.tokens.push(Token::eof(self.current));
+        // A string left open inside the interpolation has already been reported
+        let unterminated = |err: &LoxError| err.message() == "Unterminated string.";
+        if !self.interpolations.is_empty() && !self.errors.last().is_some_and(unterminated) {
+            self.errors
+                .push(LoxError::error(self.line, "Unterminated string."));
+        }
+        self.tokens.push(Token::eof(self.line));
 
-        match had_error {
+        match self.errors.last() {
             None => Ok(&self.tokens),
-            Some(e) => Err(e),
+            Some(e) => Err(e.clone()),
         }
     }
 
+    pub fn errors(&self) -> &[LoxError] {
+        &self.errors
+    }
+
+    pub fn comments(&self) -> &[usize] {
+        &self.comments
+    }
+
     pub fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
@@ -52,14 +88,34 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace);
+            }
+            '}' => match self.interpolations.last_mut() {
+                // The end of an interpolated expression: back to the string
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string()?;
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightBrace);
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
+            ':' => self.add_token(TokenType::Colon),
             ',' => self.add_token(TokenType::Comma),
             '.' => self.add_token(TokenType::Dot),
             '-' => self.add_token(TokenType::Minus),
             '+' => self.add_token(TokenType::Plus),
             ';' => self.add_token(TokenType::Semicolon),
             '*' => self.add_token(TokenType::Star),
+            '%' => self.add_token(TokenType::Percent),
             '!' => {
                 let tok = if self.next_char_matches('=') {
                     TokenType::BangEqual
@@ -92,10 +148,19 @@ impl Scanner {
                 };
                 self.add_token(tok);
             }
+            // Floor division is spelled `~/` because `//` starts a comment
+            '~' => {
+                if self.next_char_matches('/') {
+                    self.add_token(TokenType::TildeSlash);
+                } else {
+                    return Err(LoxError::error(self.line, "Unexpected character."));
+                }
+            }
             '/' => {
                 if self.next_char_matches('/') {
                     // A comment extends to the end of the line
-                    while self.peek() != None && self.peek() != Some('\n') && !self.is_at_end() {
+                    self.comments.push(self.line);
+                    while self.peek().is_some() && self.peek() != Some('\n') && !self.is_at_end() {
                         self.advance();
                     }
                 } else {
@@ -109,7 +174,7 @@ impl Scanner {
             }
             _ => {
                 if Scanner::is_digit(Some(c)) {
-                    self.number();
+                    self.number()?;
                 } else if Scanner::is_alpha(Some(c)) {
                     self.identifier();
                 } else {
@@ -120,8 +185,21 @@ impl Scanner {
         Ok(())
     }
 
+    // Scans from the opening `"`, or from the `}` that ends an interpolated
+    // expression, up to the closing `"` or the next `${`. The text in between
+    // is the token's value.
     fn string(&mut self) -> Result<(), LoxError> {
-        while self.peek() != None && self.peek() != Some('"') && !self.is_at_end() {
+        while self.peek().is_some() && self.peek() != Some('"') && !self.is_at_end() {
+            if self.dialect == Dialect::Extended
+                && self.peek() == Some('$')
+                && self.peek_next() == Some('{')
+            {
+                let value: String = self.source[self.start + 1..self.current].iter().collect();
+                self.current += 2;
+                self.interpolations.push(0);
+                self.add_token_object(TokenType::Interpolation, Some(Object::Str(value)));
+                return Ok(());
+            }
             if self.peek() == Some('\n') {
                 self.line += 1;
             }
@@ -143,7 +221,7 @@ impl Scanner {
         Ok(())
     }
 
-    fn number(&mut self) {
+    fn number(&mut self) -> Result<(), LoxError> {
         while Scanner::is_digit(self.peek()) {
             self.advance();
         }
@@ -158,9 +236,23 @@ impl Scanner {
             }
         }
 
+        // Literals without a fractional part are integers, and become bignums
+        // when they don't fit in an i64
         let value: String = self.source[self.start..self.current].iter().collect();
-        let num: f64 = value.parse().unwrap();
-        self.add_token_object(TokenType::Number, Some(Object::Num(num)));
+        let num = if value.contains('.') {
+            value.parse().map(Object::Num).ok()
+        } else if let Ok(n) = value.parse::<i64>() {
+            Some(Object::Int(n))
+        } else {
+            value.parse().map(Object::BigInt).ok()
+        };
+        match num {
+            Some(num) => {
+                self.add_token_object(TokenType::Number, Some(num));
+                Ok(())
+            }
+            None => Err(LoxError::error(self.line, "Invalid number.")),
+        }
     }
 
     fn identifier(&mut self) {
@@ -169,13 +261,29 @@ impl Scanner {
         }
 
         let text: String = self.source[self.start..self.current].iter().collect();
-        if let Some(ttype) = Scanner::keyword(&text) {
-            self.add_token(ttype);
-        } else {
-            self.add_token(TokenType::Identifier)
+        match Scanner::keyword(&text) {
+            // The strict dialect leaves the extensions' keywords free for names
+            Some(ttype) if self.dialect == Dialect::Extended || !Scanner::is_extension(ttype) => {
+                self.add_token(ttype)
+            }
+            _ => self.add_token(TokenType::Identifier),
         }
     }
 
+    // Keywords the reference implementation doesn't have
+    fn is_extension(ttype: TokenType) -> bool {
+        matches!(
+            ttype,
+            TokenType::As
+                | TokenType::From
+                | TokenType::Import
+                | TokenType::Try
+                | TokenType::Catch
+                | TokenType::Finally
+                | TokenType::Throw
+        )
+    }
+
     fn is_digit(ch: Option<char>) -> bool {
         matches!(ch, Some('0'..='9'))
     }
@@ -233,25 +341,130 @@ impl Scanner {
         }
     }
 
-    fn keyword(check: &str) -> Option<TokenType> {
+    pub fn keyword(check: &str) -> Option<TokenType> {
         match check {
             "and" => Some(TokenType::And),
+            "as" => Some(TokenType::As),
+            "catch" => Some(TokenType::Catch),
             "class" => Some(TokenType::Class),
             "else" => Some(TokenType::Else),
             "false" => Some(TokenType::False),
+            "finally" => Some(TokenType::Finally),
             "for" => Some(TokenType::For),
+            "from" => Some(TokenType::From),
             "fun" => Some(TokenType::Fun),
             "if" => Some(TokenType::If),
+            "import" => Some(TokenType::Import),
             "nil" => Some(TokenType::Nil),
             "or" => Some(TokenType::Or),
             "print" => Some(TokenType::Print),
             "return" => Some(TokenType::Return),
             "super" => Some(TokenType::Super),
             "this" => Some(TokenType::This),
+            "throw" => Some(TokenType::Throw),
             "true" => Some(TokenType::True),
+            "try" => Some(TokenType::Try),
             "var" => Some(TokenType::Var),
             "while" => Some(TokenType::While),
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_are_listed() {
+        for keyword in KEYWORDS {
+            assert!(Scanner::keyword(keyword).is_some(), "{keyword}");
+        }
+        let tokens = Scanner::new(KEYWORDS.join(" "))
+            .scan_tokens()
+            .unwrap()
+            .clone();
+        assert!(tokens.iter().all(|token| !token.is(TokenType::Identifier)));
+    }
+
+    #[test]
+    fn strict_extension_keywords_are_names() {
+        let mut scanner = Scanner::new("import from as try catch finally throw var".to_string());
+        scanner.set_dialect(Dialect::Strict);
+        let types: Vec<TokenType> = scanner
+            .scan_tokens()
+            .unwrap()
+            .iter()
+            .map(|token| token.token_type())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::Var,
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn floor_division_is_not_a_comment() {
+        let mut scanner = Scanner::new("7 ~/ 2 // 3".to_string());
+        let types: Vec<TokenType> = scanner
+            .scan_tokens()
+            .unwrap()
+            .iter()
+            .map(|token| token.token_type())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                TokenType::Number,
+                TokenType::TildeSlash,
+                TokenType::Number,
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_strings_have_no_interpolations() {
+        let mut scanner = Scanner::new("\"${a}\"".to_string());
+        scanner.set_dialect(Dialect::Strict);
+        let tokens = scanner.scan_tokens().unwrap();
+        assert_eq!(tokens[0].literal, Some(Object::Str("${a}".to_string())));
+        assert!(tokens[1].is(TokenType::Eof));
+    }
+
+    #[test]
+    fn interpolations_are_split_into_tokens() {
+        let mut scanner = Scanner::new("\"a${ {}[b] }c${d}\"".to_string());
+        let types: Vec<TokenType> = scanner
+            .scan_tokens()
+            .unwrap()
+            .iter()
+            .map(|token| token.token_type())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                TokenType::Interpolation,
+                TokenType::LeftBrace,
+                TokenType::RightBrace,
+                TokenType::LeftBracket,
+                TokenType::Identifier,
+                TokenType::RightBracket,
+                TokenType::Interpolation,
+                TokenType::Identifier,
+                TokenType::String,
+                TokenType::Eof,
+            ]
+        );
+    }
+}
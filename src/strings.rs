@@ -0,0 +1,117 @@
+use crate::convert::{FromLox, IntoLox};
+use crate::error::LoxError;
+use crate::list::{check_index, List};
+use crate::native::NativeFunction;
+use crate::object::Object;
+
+// Methods on strings, which come back bound to the string, ready to be
+// called. Positions count characters rather than bytes.
+pub fn method(string: &str, name: &str) -> Result<Object, LoxError> {
+    let s = string.to_string();
+    let function = match name {
+        "len" => NativeFunction::new(name, 0, move |_| Ok(Object::Int(s.chars().count() as i64))),
+        // From `start` up to but not including `end`
+        "substring" => NativeFunction::new(name, 2, move |args| {
+            let chars: Vec<char> = s.chars().collect();
+            let end = check_index("String", &args[1], chars.len() + 1)?;
+            let start = check_index("String", &args[0], end + 1)?;
+            Ok(chars[start..end].iter().collect::<String>().into_lox())
+        }),
+        // -1 when `part` doesn't appear
+        "indexOf" => NativeFunction::new(name, 1, move |args| {
+            let part = String::from_lox(&args[0])?;
+            let index = s
+                .find(&part)
+                .map_or(-1, |byte| s[..byte].chars().count() as i64);
+            Ok(Object::Int(index))
+        }),
+        // An empty separator splits the string into characters
+        "split" => NativeFunction::new(name, 1, move |args| {
+            let separator = String::from_lox(&args[0])?;
+            let parts: Vec<&str> = if separator.is_empty() {
+                s.char_indices()
+                    .map(|(i, c)| &s[i..i + c.len_utf8()])
+                    .collect()
+            } else {
+                s.split(separator.as_str()).collect()
+            };
+            Ok(parts.into_lox())
+        }),
+        // Joins a list of strings with this string between them
+        "join" => NativeFunction::new(name, 1, move |args| {
+            let parts = Vec::<String>::from_lox(&args[0])?;
+            Ok(parts.join(&s).into_lox())
+        }),
+        "trim" => NativeFunction::new(name, 0, move |_| Ok(s.trim().into_lox())),
+        "upper" => NativeFunction::new(name, 0, move |_| Ok(s.to_uppercase().into_lox())),
+        "lower" => NativeFunction::new(name, 0, move |_| Ok(s.to_lowercase().into_lox())),
+        // Replaces every occurrence
+        "replace" => NativeFunction::new(name, 2, move |args| {
+            let from = String::from_lox(&args[0])?;
+            let to = String::from_lox(&args[1])?;
+            Ok(s.replace(&from, &to).into_lox())
+        }),
+        "startsWith" => NativeFunction::new(name, 1, move |args| {
+            let prefix = String::from_lox(&args[0])?;
+            Ok(s.starts_with(&prefix).into_lox())
+        }),
+        "chars" => NativeFunction::new(name, 0, move |_| {
+            let chars = s.chars().map(|c| Object::Str(c.to_string())).collect();
+            Ok(List::new(chars).into())
+        }),
+        _ => {
+            return Err(LoxError::host_error(&format!(
+                "Undefined property '{name}'."
+            )))
+        }
+    };
+    Ok(function.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(string: &str, name: &str, args: &[Object]) -> Result<Object, LoxError> {
+        match method(string, name)? {
+            Object::NativeFunction(function) => function.call(args),
+            _ => panic!("{name} should be a function"),
+        }
+    }
+
+    fn text(s: &str) -> Object {
+        Object::Str(s.to_string())
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        assert_eq!(call("héllo", "len", &[]).unwrap(), Object::Int(5));
+        assert_eq!(
+            call("héllo", "indexOf", &[text("l")]).unwrap(),
+            Object::Int(2)
+        );
+        assert_eq!(
+            call("héllo", "indexOf", &[text("z")]).unwrap(),
+            Object::Int(-1)
+        );
+        assert_eq!(
+            call("héllo", "substring", &[Object::Int(1), Object::Int(3)]).unwrap(),
+            text("él")
+        );
+        let err = call("abc", "substring", &[Object::Int(0), Object::Int(4)]).unwrap_err();
+        assert_eq!(err.message(), "String index 4 is out of bounds.");
+    }
+
+    #[test]
+    fn splits_and_joins() {
+        let parts = call("a,b,,c", "split", &[text(",")]).unwrap();
+        assert_eq!(parts.to_string(), "[\"a\", \"b\", \"\", \"c\"]");
+        assert_eq!(call("-", "join", &[parts]).unwrap(), text("a-b--c"));
+        assert_eq!(
+            call("ab", "split", &[text("")]).unwrap().to_string(),
+            "[\"a\", \"b\"]"
+        );
+        let err = call(",", "join", &[vec![1i64].into_lox()]).unwrap_err();
+        assert_eq!(err.message(), "Expected a string but got int.");
+    }
+}
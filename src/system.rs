@@ -0,0 +1,183 @@
+use crate::convert::{FromLox, IntoLox};
+use crate::error::LoxError;
+use crate::interpreter::Capabilities;
+use crate::native::NativeFunction;
+use crate::object::Object;
+use std::cell::{Cell, RefCell};
+use std::io::{self, BufRead, BufReader};
+use std::rc::Rc;
+
+// What scripts can reach outside the interpreter: its input, the arguments
+// the host passed, and whatever the capabilities allow. The interpreter and
+// the natives below share it, so a change made by the host is seen by both.
+pub(crate) struct System {
+    pub capabilities: Cell<Capabilities>,
+    pub args: RefCell<Vec<String>>,
+    pub input: RefCell<Box<dyn BufRead>>,
+}
+
+impl Default for System {
+    fn default() -> System {
+        System {
+            capabilities: Cell::new(Capabilities::default()),
+            args: RefCell::new(Vec::new()),
+            input: RefCell::new(Box::new(BufReader::new(io::stdin()))),
+        }
+    }
+}
+
+impl System {
+    // Returns None at the end of the input; the line ending is dropped
+    pub fn read_line(&self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.input.borrow_mut().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
+    // Denied calls name the flag that would allow them
+    fn check(&self, function: &str, allowed: bool, flag: &str) -> Result<(), LoxError> {
+        if allowed {
+            Ok(())
+        } else {
+            Err(LoxError::host_error(&format!(
+                "{function}() is not allowed; run with --allow-{flag} to allow it."
+            )))
+        }
+    }
+}
+
+// The natives that talk to the outside world
+pub(crate) fn natives(system: &Rc<System>) -> Vec<NativeFunction> {
+    let native =
+        |name: &str, arity, function: fn(&System, &[Object]) -> Result<Object, LoxError>| {
+            let system = Rc::clone(system);
+            NativeFunction::new(name, arity, move |args| function(&system, args))
+        };
+    vec![
+        // nil at the end of the input
+        native("readLine", 0, |system, _| {
+            system.check("readLine", system.capabilities.get().read, "read")?;
+            let line = system
+                .read_line()
+                .map_err(|err| LoxError::host_error(&format!("Could not read input: {err}.")))?;
+            Ok(line.map_or(Object::Nil, Object::Str))
+        }),
+        native("readFile", 1, |system, args| {
+            system.check("readFile", system.capabilities.get().read, "read")?;
+            let path = String::from_lox(&args[0])?;
+            std::fs::read_to_string(&path)
+                .map(Object::Str)
+                .map_err(|err| LoxError::host_error(&format!("Could not read {path}: {err}.")))
+        }),
+        // Replaces the file if it exists
+        native("writeFile", 2, |system, args| {
+            system.check("writeFile", system.capabilities.get().write, "write")?;
+            let path = String::from_lox(&args[0])?;
+            let contents = String::from_lox(&args[1])?;
+            std::fs::write(&path, contents)
+                .map(|()| Object::Nil)
+                .map_err(|err| LoxError::host_error(&format!("Could not write {path}: {err}.")))
+        }),
+        native("args", 0, |system, _| {
+            Ok(system.args.borrow().clone().into_lox())
+        }),
+        // nil when the variable isn't set
+        native("env", 1, |system, args| {
+            system.check("env", system.capabilities.get().env, "env")?;
+            let name = String::from_lox(&args[0])?;
+            Ok(std::env::var(name).map_or(Object::Nil, Object::Str))
+        }),
+        // Ends the script; whole floats are accepted since the strict dialect
+        // has no integers
+        native("exit", 1, |_, args| {
+            let status = f64::from_lox(&args[0])?;
+            if status.fract() != 0.0 || !(0.0..=255.0).contains(&status) {
+                return Err(LoxError::host_error(
+                    "Exit status must be a whole number from 0 to 255.",
+                ));
+            }
+            Err(LoxError::exit(status as i32))
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ErrorKind;
+    use crate::interpreter::Capabilities;
+    use crate::lox::Lox;
+    use crate::object::Object;
+
+    #[test]
+    fn denied_by_default() {
+        let lox = Lox::new();
+        for call in [
+            "readLine()",
+            "readFile(\"x\")",
+            "writeFile(\"x\", \"\")",
+            "env(\"HOME\")",
+        ] {
+            let err = lox.eval(&format!("{call};")).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Runtime);
+            assert!(
+                err.message().contains("is not allowed; run with --allow-"),
+                "{call}"
+            );
+        }
+        let err = lox.eval("readFile(\"x\");").unwrap_err();
+        assert_eq!(
+            err.message(),
+            "readFile() is not allowed; run with --allow-read to allow it."
+        );
+    }
+
+    #[test]
+    fn files_and_input() {
+        let path = std::env::temp_dir().join(format!("lox-system-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap().replace('\\', "/");
+        let mut lox = Lox::new();
+        lox.interpreter_mut().set_capabilities(Capabilities::all());
+        lox.interpreter_mut()
+            .set_args(vec!["a".to_string(), "b".to_string()]);
+        lox.set_input(Box::new("line\n".as_bytes()));
+
+        lox.eval(&format!("writeFile(\"{path}\", \"hi\");"))
+            .unwrap();
+        assert_eq!(
+            lox.eval(&format!("readFile(\"{path}\");")).unwrap(),
+            Object::Str("hi".to_string())
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert!(lox.eval(&format!("readFile(\"{path}\");")).is_err());
+
+        assert_eq!(
+            lox.eval("readLine();").unwrap(),
+            Object::Str("line".to_string())
+        );
+        assert_eq!(lox.eval("readLine();").unwrap(), Object::Nil);
+        assert_eq!(lox.eval("args();").unwrap().to_string(), "[\"a\", \"b\"]");
+        assert_eq!(lox.eval("env(\"LOX_SURELY_UNSET\");").unwrap(), Object::Nil);
+    }
+
+    #[test]
+    fn exit_unwinds_the_script() {
+        let lox = Lox::new();
+        let err = lox
+            .eval("var a = 1; try { exit(3); } catch (e) { a = 2; } finally { a = a + 10; }")
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Exit(3));
+        // `catch` doesn't stop it, though `finally` still runs
+        assert_eq!(lox.eval("a;").unwrap(), Object::Int(11));
+        assert!(lox
+            .eval("exit(256);")
+            .is_err_and(|err| err.kind() == ErrorKind::Runtime));
+    }
+}
@@ -2,7 +2,7 @@ use crate::object::Object;
 use crate::token_type::TokenType;
 use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub ttype: TokenType,
     pub lexeme: String,
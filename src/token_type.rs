@@ -5,11 +5,15 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
     Minus,
     Plus,
     Semicolon,
+    Percent,
     Slash,
     Star,
     // One or two character tokens
@@ -21,25 +25,34 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    TildeSlash, // Integer division
     // Literals
     Identifier,
     String,
+    Interpolation, // The part of a string before `${`
     Number,
     // Keywords
     And,
+    As,
+    Catch,
     Class,
     Else,
     False,
+    Finally,
     Fun,
     For,
+    From,
     If,
+    Import,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
     Eof,
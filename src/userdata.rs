@@ -0,0 +1,259 @@
+use crate::error::LoxError;
+use crate::native::NativeFunction;
+use crate::object::Object;
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+type Method = dyn Fn(&mut dyn Any, &[Object]) -> Result<Object, LoxError>;
+type Getter = dyn Fn(&dyn Any) -> Object;
+type Setter = dyn Fn(&mut dyn Any, Object) -> Result<(), LoxError>;
+
+struct MethodEntry {
+    arity: usize,
+    method: Rc<Method>,
+}
+
+// Describes a Rust type to scripts: its name and the methods and properties
+// reachable through `.`. Built once by the host and shared by its instances.
+//
+//   let class = UserClass::builder::<Vector3>("Vector3")
+//       .getter("x", |v| v.x.into_lox())
+//       .method("length", 0, |v, _| Ok(v.length().into_lox()))
+//       .build();
+pub struct UserClass {
+    name: String,
+    type_id: TypeId,
+    methods: HashMap<String, MethodEntry>,
+    getters: HashMap<String, Box<Getter>>,
+    setters: HashMap<String, Box<Setter>>,
+}
+
+// Instances are checked against the class's type when they're created, so
+// downcasting to T can't fail.
+fn downcast<T: 'static>(data: &dyn Any) -> &T {
+    data.downcast_ref().expect("userdata of the wrong type")
+}
+
+fn downcast_mut<T: 'static>(data: &mut dyn Any) -> &mut T {
+    data.downcast_mut().expect("userdata of the wrong type")
+}
+
+impl UserClass {
+    pub fn builder<T: 'static>(name: &str) -> UserClassBuilder<T> {
+        UserClassBuilder {
+            class: UserClass {
+                name: name.to_string(),
+                type_id: TypeId::of::<T>(),
+                methods: HashMap::new(),
+                getters: HashMap::new(),
+                setters: HashMap::new(),
+            },
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// A UserClass under construction, which knows the Rust type its closures take
+pub struct UserClassBuilder<T> {
+    class: UserClass,
+    marker: std::marker::PhantomData<T>,
+}
+
+impl<T: 'static> UserClassBuilder<T> {
+    pub fn method<F>(mut self, name: &str, arity: usize, method: F) -> UserClassBuilder<T>
+    where
+        F: Fn(&mut T, &[Object]) -> Result<Object, LoxError> + 'static,
+    {
+        let method: Rc<Method> = Rc::new(move |data, args| method(downcast_mut(data), args));
+        self.class
+            .methods
+            .insert(name.to_string(), MethodEntry { arity, method });
+        self
+    }
+
+    // A property without a setter is read-only
+    pub fn getter<G>(mut self, name: &str, getter: G) -> UserClassBuilder<T>
+    where
+        G: Fn(&T) -> Object + 'static,
+    {
+        self.class.getters.insert(
+            name.to_string(),
+            Box::new(move |data| getter(downcast(data))),
+        );
+        self
+    }
+
+    pub fn setter<S>(mut self, name: &str, setter: S) -> UserClassBuilder<T>
+    where
+        S: Fn(&mut T, Object) -> Result<(), LoxError> + 'static,
+    {
+        self.class.setters.insert(
+            name.to_string(),
+            Box::new(move |data, value| setter(downcast_mut(data), value)),
+        );
+        self
+    }
+
+    pub fn property<G, S>(self, name: &str, getter: G, setter: S) -> UserClassBuilder<T>
+    where
+        G: Fn(&T) -> Object + 'static,
+        S: Fn(&mut T, Object) -> Result<(), LoxError> + 'static,
+    {
+        self.getter(name, getter).setter(name, setter)
+    }
+
+    pub fn build(self) -> Rc<UserClass> {
+        Rc::new(self.class)
+    }
+}
+
+// An instance of a host type. Copies of the value share the same data, and
+// two values are equal only when they are the same instance.
+#[derive(Clone)]
+pub struct UserData {
+    class: Rc<UserClass>,
+    data: Rc<RefCell<dyn Any>>,
+}
+
+impl UserData {
+    // Panics if the value isn't of the type the class was built for
+    pub fn new<T: 'static>(class: &Rc<UserClass>, value: T) -> UserData {
+        assert!(
+            class.type_id == TypeId::of::<T>(),
+            "{} instance created from the wrong type",
+            class.name
+        );
+        UserData {
+            class: Rc::clone(class),
+            data: Rc::new(RefCell::new(value)),
+        }
+    }
+
+    pub fn class(&self) -> &UserClass {
+        &self.class
+    }
+
+    pub fn borrow<T: 'static>(&self) -> Option<Ref<'_, T>> {
+        Ref::filter_map(self.data.borrow(), |data| data.downcast_ref()).ok()
+    }
+
+    pub fn same_instance(&self, other: &UserData) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+
+    // Identifies the instance, e.g. for hashing it as a map key
+    pub(crate) fn address(&self) -> *const () {
+        Rc::as_ptr(&self.data) as *const ()
+    }
+
+    // Properties take priority over methods; a method comes back bound to
+    // this instance, ready to be called.
+    pub fn get(&self, name: &str) -> Result<Object, LoxError> {
+        if let Some(getter) = self.class.getters.get(name) {
+            let data = self.data.try_borrow().map_err(|_| self.busy())?;
+            return Ok(getter(&*data));
+        }
+        if let Some(entry) = self.class.methods.get(name) {
+            let instance = self.clone();
+            let method = Rc::clone(&entry.method);
+            let function = NativeFunction::new(name, entry.arity, move |args| {
+                let mut data = instance
+                    .data
+                    .try_borrow_mut()
+                    .map_err(|_| instance.busy())?;
+                method(&mut *data, args)
+            });
+            return Ok(function.into());
+        }
+        Err(LoxError::host_error(&format!(
+            "Undefined property '{name}'."
+        )))
+    }
+
+    pub fn set(&self, name: &str, value: Object) -> Result<(), LoxError> {
+        match self.class.setters.get(name) {
+            Some(setter) => {
+                let mut data = self.data.try_borrow_mut().map_err(|_| self.busy())?;
+                setter(&mut *data, value)
+            }
+            None => Err(LoxError::host_error(&format!(
+                "Can't set property '{name}'."
+            ))),
+        }
+    }
+
+    fn busy(&self) -> LoxError {
+        LoxError::host_error(&format!("{} instance is already in use.", self.class.name))
+    }
+}
+
+impl fmt::Debug for UserData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "UserData({})", self.class.name)
+    }
+}
+
+impl From<UserData> for Object {
+    fn from(data: UserData) -> Object {
+        Object::UserData(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{FromLox, IntoLox};
+
+    struct Counter {
+        count: i64,
+    }
+
+    fn counter_class() -> Rc<UserClass> {
+        UserClass::builder::<Counter>("Counter")
+            .property(
+                "count",
+                |c| c.count.into_lox(),
+                |c, value| {
+                    c.count = i64::from_lox(&value)?;
+                    Ok(())
+                },
+            )
+            .method("bump", 1, |c, args| {
+                c.count += i64::from_lox(&args[0])?;
+                Ok(c.count.into_lox())
+            })
+            .build()
+    }
+
+    #[test]
+    fn properties_and_methods() {
+        let counter = UserData::new(&counter_class(), Counter { count: 1 });
+        counter.set("count", Object::Int(5)).unwrap();
+        assert_eq!(counter.get("count").unwrap(), Object::Int(5));
+
+        let Object::NativeFunction(bump) = counter.get("bump").unwrap() else {
+            panic!("bump should be a function");
+        };
+        assert_eq!(bump.call(&[Object::Int(2)]).unwrap(), Object::Int(7));
+        assert_eq!(counter.borrow::<Counter>().unwrap().count, 7);
+
+        assert!(counter.get("missing").is_err());
+        assert!(counter.set("bump", Object::Nil).is_err());
+    }
+
+    #[test]
+    fn equality_is_identity() {
+        let class = counter_class();
+        let a = Object::from(UserData::new(&class, Counter { count: 0 }));
+        let b = Object::from(UserData::new(&class, Counter { count: 0 }));
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,112 @@
+// Checks the command line's subcommands and exit statuses
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn lox(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lox-ast"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("could not start lox-ast");
+    // A script that doesn't read its input can exit before it's all written
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn runs_inline_code_and_stdin() {
+    let output = lox(&["-e", "print 1 + 2;"], "");
+    assert_eq!(stdout(&output), "3\n");
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = lox(&["run", "-"], "var a = \"in\"; print a;");
+    assert_eq!(stdout(&output), "\"in\"\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn exit_statuses() {
+    assert_eq!(lox(&["-e", "print;"], "").status.code(), Some(65));
+    assert_eq!(lox(&["-e", "print x;"], "").status.code(), Some(70));
+    assert_eq!(lox(&["no/such/file.lox"], "").status.code(), Some(66));
+    assert_eq!(lox(&["--bogus"], "").status.code(), Some(64));
+    assert_eq!(lox(&["run"], "").status.code(), Some(64));
+    assert_eq!(lox(&["-e"], "").status.code(), Some(64));
+    assert_eq!(lox(&["-e", "exit(3);"], "").status.code(), Some(3));
+}
+
+#[test]
+fn scripts_get_their_arguments() {
+    let output = lox(&["-e", "print args();", "a", "--strict"], "");
+    assert_eq!(stdout(&output), "[\"a\", \"--strict\"]\n");
+}
+
+#[test]
+fn io_needs_permission() {
+    let output = lox(&["-e", "print readLine();"], "typed\n");
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "readLine() is not allowed; run with --allow-read to allow it.\n\
+         [line 1] in readLine()\n\
+         [line 1] in script\n"
+    );
+
+    let output = lox(&["--allow-read", "-e", "print readLine();"], "typed\n");
+    assert_eq!(stdout(&output), "\"typed\"\n");
+}
+
+#[test]
+fn check_does_not_run() {
+    let output = lox(&["check", "-e", "print x;"], "");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(lox(&["check", "-e", "print"], "").status.code(), Some(65));
+
+    // An operation bound to fail only fails when it runs
+    let output = lox(&["check", "-e", "print -\"x\";"], "");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn modules_that_do_not_compile_are_compile_errors() {
+    let dir = std::env::temp_dir().join(format!("lox-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("broken.lox"), "var a = ;").unwrap();
+    let main = dir.join("main.lox");
+    std::fs::write(&main, "import \"broken.lox\" as b;").unwrap();
+    let output = lox(&[main.to_str().unwrap()], "");
+    assert_eq!(output.status.code(), Some(65));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn inspects_scripts() {
+    let output = lox(&["fmt", "-"], "var a=1;{print (a+2)*3;}");
+    assert_eq!(
+        stdout(&output),
+        "var a = 1;\n{\n    print (a + 2) * 3;\n}\n"
+    );
+
+    // Comments would be lost
+    let output = lox(&["fmt", "-"], "print 1;\n// one\n");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "[line 2] Error: Can't format a script that has comments.\n"
+    );
+    assert_eq!(output.status.code(), Some(65));
+
+    let output = lox(&["ast", "-e", "print -a;"], "");
+    assert_eq!(stdout(&output), "(print (- a))\n");
+
+    let output = lox(&["tokens", "-e", "a"], "");
+    assert_eq!(stdout(&output), "Identifier a None\nEof  None\n");
+}
@@ -0,0 +1,183 @@
+// Property tests for the front end: the scanner, parser and interpreter must
+// never panic on arbitrary input, formatting a tree and parsing it again must
+// give back the same tree, and constant folding must not change what an
+// expression evaluates to.
+use lox_ast::expr::*;
+use lox_ast::formatter::Formatter;
+use lox_ast::interpreter::Interpreter;
+use lox_ast::object::Object;
+use lox_ast::optimizer::ConstantFolder;
+use lox_ast::parser::Parser;
+use lox_ast::scanner::Scanner;
+use lox_ast::stmt::*;
+use lox_ast::token::Token;
+use lox_ast::token_type::TokenType;
+use proptest::prelude::*;
+
+// Characters that make up Lox tokens, so random sources get past the scanner
+// often enough to exercise the parser.
+const LOX_SOURCE: &str = "[-+*/%~!=<>(){}\\[\\];.,a-z0-9\" \n]{0,100}";
+
+// Binding power of an expression, matching the parser's grammar levels
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Binary(b) => binary_precedence(b.operator.token_type()),
+        Expr::Unary(_) => 5,
+        _ => 6,
+    }
+}
+
+fn binary_precedence(ttype: TokenType) -> u8 {
+    match ttype {
+        TokenType::EqualEqual | TokenType::BangEqual => 1,
+        TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => 2,
+        TokenType::Minus | TokenType::Plus => 3,
+        _ => 4,
+    }
+}
+
+fn group(expr: Expr) -> Expr {
+    Expr::Grouping(GroupingExpr {
+        expression: Box::new(expr),
+    })
+}
+
+// Builds the trees the parser could have produced: an operand that binds
+// more loosely than its operator is wrapped in a grouping, and since binary
+// operators are left associative the right operand must bind strictly tighter.
+fn binary(left: Expr, (ttype, lexeme): (TokenType, &str), right: Expr) -> Expr {
+    let level = binary_precedence(ttype);
+    let left = if precedence(&left) >= level {
+        left
+    } else {
+        group(left)
+    };
+    let right = if precedence(&right) > level {
+        right
+    } else {
+        group(right)
+    };
+    Expr::Binary(BinaryExpr {
+        left: Box::new(left),
+        operator: Token::new(ttype, lexeme.to_string(), None, 1),
+        right: Box::new(right),
+    })
+}
+
+fn unary((ttype, lexeme): (TokenType, &str), right: Expr) -> Expr {
+    let right = if precedence(&right) >= 5 {
+        right
+    } else {
+        group(right)
+    };
+    Expr::Unary(UnaryExpr {
+        operator: Token::new(ttype, lexeme.to_string(), None, 1),
+        right: Box::new(right),
+    })
+}
+
+// Negative numbers aren't literals: the parser sees a unary minus
+fn literal() -> impl Strategy<Value = Object> {
+    prop_oneof![
+        Just(Object::Nil),
+        any::<bool>().prop_map(Object::Bool),
+        (0..i64::MAX).prop_map(Object::Int),
+        "[1-9][0-9]{19,30}".prop_map(|n| Object::BigInt(n.parse().unwrap())),
+        (0u64..100_000_000).prop_map(|n| Object::Num(n as f64 / 100.0)),
+        "[a-z0-9 ]{0,8}".prop_map(Object::Str),
+    ]
+}
+
+fn binary_operator() -> impl Strategy<Value = (TokenType, &'static str)> {
+    prop::sample::select(vec![
+        (TokenType::EqualEqual, "=="),
+        (TokenType::BangEqual, "!="),
+        (TokenType::Greater, ">"),
+        (TokenType::GreaterEqual, ">="),
+        (TokenType::Less, "<"),
+        (TokenType::LessEqual, "<="),
+        (TokenType::Minus, "-"),
+        (TokenType::Plus, "+"),
+        (TokenType::Slash, "/"),
+        (TokenType::Star, "*"),
+        (TokenType::TildeSlash, "~/"),
+        (TokenType::Percent, "%"),
+    ])
+}
+
+fn unary_operator() -> impl Strategy<Value = (TokenType, &'static str)> {
+    prop::sample::select(vec![(TokenType::Minus, "-"), (TokenType::Bang, "!")])
+}
+
+fn expression() -> impl Strategy<Value = Expr> {
+    let leaf = literal().prop_map(|value| Expr::Literal(LiteralExpr { value: Some(value) }));
+    leaf.prop_recursive(6, 64, 2, |inner| {
+        prop_oneof![
+            (inner.clone(), binary_operator(), inner.clone())
+                .prop_map(|(left, op, right)| binary(left, op, right)),
+            (unary_operator(), inner.clone()).prop_map(|(op, right)| unary(op, right)),
+            inner.prop_map(group),
+        ]
+    })
+}
+
+fn run(source: &str) {
+    let mut scanner = Scanner::new(source.to_string());
+    let Ok(tokens) = scanner.scan_tokens() else {
+        return;
+    };
+    let Ok(statements) = Parser::new(tokens).parse() else {
+        return;
+    };
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(Box::new(std::io::sink()));
+    if let Ok(statements) = ConstantFolder::new(&interpreter).optimize(statements) {
+        let _ = interpreter.interpret(&statements);
+    }
+}
+
+// NaN never equals itself, so values are compared by how they print
+fn evaluate(interpreter: &Interpreter, expr: &Expr) -> Option<String> {
+    expr.accept(interpreter).ok().map(|value| value.to_string())
+}
+
+proptest! {
+    #[test]
+    fn scanner_never_panics(source in "\\PC{0,200}") {
+        let _ = Scanner::new(source).scan_tokens();
+    }
+
+    #[test]
+    fn pipeline_never_panics(source in LOX_SOURCE) {
+        run(&source);
+    }
+
+    #[test]
+    fn format_round_trips(expr in expression()) {
+        let source = format!("{};", Formatter.format_expr(&expr).unwrap());
+        let mut scanner = Scanner::new(source.clone());
+        let tokens = scanner.scan_tokens().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        match statements.as_slice() {
+            [Stmt::Expression(stmt)] => prop_assert_eq!(&stmt.expression, &expr, "source: {}", source),
+            _ => prop_assert!(false, "{} did not parse as one expression", source),
+        }
+    }
+
+    #[test]
+    fn folding_preserves_meaning(expr in expression()) {
+        let interpreter = Interpreter::new();
+        let expected = evaluate(&interpreter, &expr);
+
+        let statements = vec![Stmt::Expression(ExpressionStmt { expression: expr })];
+        match ConstantFolder::new(&interpreter).optimize(statements) {
+            Ok(folded) => match folded.as_slice() {
+                [Stmt::Expression(stmt)] => {
+                    prop_assert_eq!(evaluate(&interpreter, &stmt.expression), expected)
+                }
+                _ => prop_assert!(false, "folding changed the statements"),
+            },
+            Err(_) => prop_assert_eq!(expected, None),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+// Runs every `.lox` file under tests/suite through the interpreter binary and
+// checks its output against the annotations in the file, in the format used
+// by the reference implementation's test suite:
+//
+//   print 1;        // expect: 1
+//   -"s";           // expect runtime error: Operand must be a number.
+//   print;          // Error at ';': Expect expression.
+//   // [line 3] Error: Unexpected character.
+//
+// The scripts run in the strict dialect, since that is what the expectations
+// describe, apart from those under `extended/`, which cover the extensions
+// that the strict dialect turns off.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const EXIT_COMPILE_ERROR: i32 = 65;
+const EXIT_RUNTIME_ERROR: i32 = 70;
+
+struct Expectations {
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+    exit_code: i32,
+}
+
+fn parse_expectations(source: &str) -> Expectations {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut compile_error = false;
+    let mut runtime_error = false;
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let Some((_, comment)) = line.split_once("//") else {
+            continue;
+        };
+        let comment = comment.trim_start();
+
+        if let Some(expected) = comment.strip_prefix("expect: ") {
+            stdout.push(expected.to_string());
+        } else if let Some(message) = comment.strip_prefix("expect runtime error: ") {
+            stderr.push(message.to_string());
+            stderr.push(format!("[line {line_number}]"));
+            runtime_error = true;
+        } else if comment.starts_with("Error") {
+            stderr.push(format!("[line {line_number}] {comment}"));
+            compile_error = true;
+        } else if comment.starts_with("[line ") {
+            stderr.push(comment.to_string());
+            compile_error = true;
+        } else if let Some(error) = comment.strip_prefix("[java line ") {
+            // Only the tree-walking interpreter's variant applies to us
+            stderr.push(format!("[line {error}"));
+            compile_error = true;
+        }
+    }
+
+    let exit_code = if compile_error {
+        EXIT_COMPILE_ERROR
+    } else if runtime_error {
+        EXIT_RUNTIME_ERROR
+    } else {
+        0
+    };
+
+    Expectations {
+        stdout,
+        stderr,
+        exit_code,
+    }
+}
+
+fn lox_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .expect("could not read test directory")
+        .map(|entry| entry.expect("could not read test directory").path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            lox_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "lox") {
+            files.push(path);
+        }
+    }
+}
+
+fn run_test(path: &Path, strict: bool) -> Result<(), String> {
+    let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let expected = parse_expectations(&source);
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_lox-ast"));
+    if strict {
+        command.arg("--strict");
+    }
+    let output = command.arg(path).output().map_err(|e| e.to_string())?;
+    let stdout: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let stderr: Vec<String> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    let mut failures = Vec::new();
+    if stdout != expected.stdout {
+        failures.push(format!(
+            "stdout: expected {:?}, got {:?}",
+            expected.stdout, stdout
+        ));
+    }
+    if stderr != expected.stderr {
+        failures.push(format!(
+            "stderr: expected {:?}, got {:?}",
+            expected.stderr, stderr
+        ));
+    }
+    if output.status.code() != Some(expected.exit_code) {
+        failures.push(format!(
+            "exit code: expected {}, got {:?}",
+            expected.exit_code,
+            output.status.code()
+        ));
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n    "))
+    }
+}
+
+#[test]
+fn lox_test_suite() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/suite");
+    let mut files = Vec::new();
+    lox_files(&root, &mut files);
+    assert!(!files.is_empty(), "no tests found in {}", root.display());
+
+    let failures: Vec<String> = files
+        .iter()
+        .filter_map(|path| {
+            let strict = !path.starts_with(root.join("extended"));
+            run_test(path, strict).err().map(|failure| {
+                let name = path.strip_prefix(&root).unwrap_or(path);
+                format!("{}:\n    {failure}", name.display())
+            })
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} tests failed:\n{}",
+        failures.len(),
+        files.len(),
+        failures.join("\n")
+    );
+}